@@ -21,6 +21,16 @@ contract SP1TendermintScript is Script {
         bytes32 vkey = bytes32(vm.envBytes("TENDERMINT_VKEY_HASH"));
         uint64 trustedHeight = uint64(vm.envUint("TRUSTED_HEIGHT"));
         bytes32 trustedHeaderHash = bytes32(vm.envBytes("TRUSTED_HEADER_HASH"));
+        bytes32 chainIdHash = bytes32(vm.envBytes("CHAIN_ID_HASH"));
+        // The light client options proofs must have verified headers with.
+        uint64 trustThresholdNumerator = uint64(
+            vm.envUint("TRUST_THRESHOLD_NUMERATOR")
+        );
+        uint64 trustThresholdDenominator = uint64(
+            vm.envUint("TRUST_THRESHOLD_DENOMINATOR")
+        );
+        uint64 trustingPeriod = uint64(vm.envUint("TRUSTING_PERIOD"));
+        uint64 clockDrift = uint64(vm.envUint("CLOCK_DRIFT"));
 
         // Deployed contract addresses: https://docs.succinct.xyz/docs/verification/onchain/contract-addresses
         address sp1VerifierGateway = address(
@@ -31,6 +41,11 @@ contract SP1TendermintScript is Script {
             vkey,
             trustedHeaderHash,
             trustedHeight,
+            chainIdHash,
+            trustThresholdNumerator,
+            trustThresholdDenominator,
+            trustingPeriod,
+            clockDrift,
             sp1VerifierGateway
         );
         vm.stopBroadcast();
@@ -14,54 +14,211 @@ contract SP1Tendermint {
     uint64 public latestHeight;
     // @notice The SP1 verifier contract.
     ISP1Verifier public verifier;
+    // @notice Whether the light client was frozen by a proof of misbehaviour.
+    bool public frozen;
+    // @notice The SHA-256 hash of the chain ID of the Tendermint chain the light client tracks.
+    bytes32 public immutable chainIdHash;
+    // @notice The trust threshold that proofs must have verified headers with.
+    uint64 public immutable trustThresholdNumerator;
+    uint64 public immutable trustThresholdDenominator;
+    // @notice The trusting period and clock drift, in seconds, that proofs must have verified
+    // headers with.
+    uint64 public immutable trustingPeriod;
+    uint64 public immutable clockDrift;
+
+    // @notice Prefix of the public values of a misbehaviour proof, so they can't be mistaken for
+    // an update.
+    bytes32 public constant MISBEHAVIOUR_TAG = bytes32("SP1Tendermint/Misbehaviour");
+
+    // @notice The public values of a proof of an update. The target header's app hash is exposed as
+    // is, so that state proofs of the Tendermint chain can be verified against it.
+    struct TendermintOutput {
+        uint64 trustedHeight;
+        uint64 targetHeight;
+        bytes32 trustedHeaderHash;
+        bytes32 targetHeaderHash;
+        uint64 trustThresholdNumerator;
+        uint64 trustThresholdDenominator;
+        uint64 trustingPeriod;
+        uint64 clockDrift;
+        bytes32 chainIdHash;
+        bytes targetAppHash;
+        bytes32 targetValidatorsHash;
+        bytes32 targetNextValidatorsHash;
+    }
+
+    // @notice The public values of a proof of misbehaviour.
+    struct MisbehaviourOutput {
+        bytes32 tag;
+        uint64 trustedHeight;
+        bytes32 trustedHeaderHash;
+        uint64 conflictHeight;
+        bytes32 conflictingHeaderHash1;
+        bytes32 conflictingHeaderHash2;
+        uint64 trustThresholdNumerator;
+        uint64 trustThresholdDenominator;
+        uint64 trustingPeriod;
+        uint64 clockDrift;
+        bytes32 chainIdHash;
+    }
 
     error InvalidTrustedHeader();
+    error InvalidTrustOptions();
+    error InvalidChainId();
+    error InvalidMisbehaviour();
+    error ClientFrozen();
 
-    // @notice The constructor sets the Tendermint program verification key, the initial block hash, the initial height, and the verifier for SP1 Tendermint proofs.
+    // @notice The constructor sets the Tendermint program verification key, the initial block hash, the initial height, the chain ID hash, the light client options, and the verifier for SP1 Tendermint proofs.
     // @param _tendermintProgramVkey The verification key for the Tendermint program.
     // @param _initialBlockHash The initial block hash.
     // @param _initialHeight The initial height.
+    // @param _chainIdHash The SHA-256 hash of the Tendermint chain ID.
+    // @param _trustThresholdNumerator The numerator of the trust threshold.
+    // @param _trustThresholdDenominator The denominator of the trust threshold.
+    // @param _trustingPeriod The trusting period in seconds.
+    // @param _clockDrift The clock drift in seconds.
     // @param _verifier The address of the SP1 verifier contract.
     constructor(
         bytes32 _tendermintProgramVkeyHash,
         bytes32 _initialBlockHash,
         uint64 _initialHeight,
+        bytes32 _chainIdHash,
+        uint64 _trustThresholdNumerator,
+        uint64 _trustThresholdDenominator,
+        uint64 _trustingPeriod,
+        uint64 _clockDrift,
         address _verifier
     ) {
         tendermintProgramVkeyHash = _tendermintProgramVkeyHash;
         latestHeader = _initialBlockHash;
         latestHeight = _initialHeight;
+        chainIdHash = _chainIdHash;
+        trustThresholdNumerator = _trustThresholdNumerator;
+        trustThresholdDenominator = _trustThresholdDenominator;
+        trustingPeriod = _trustingPeriod;
+        clockDrift = _clockDrift;
         verifier = ISP1Verifier(_verifier);
     }
 
     // @notice Verify an SP1 Tendermint proof.
     // @param proof The proof to verified. Should correspond to the supplied `publicValues`.
     // @param publicValues The public values to verify the proof against. The `publicValues` is the
-    // ABI-encoded tuple: (trustedHeight, targetHeight, trustedHeaderHash, targetHeaderHash)
+    // ABI-encoded `TendermintOutput`: (trustedHeight, targetHeight, trustedHeaderHash,
+    // targetHeaderHash, trustThresholdNumerator, trustThresholdDenominator, trustingPeriod,
+    // clockDrift, chainIdHash, targetAppHash, targetValidatorsHash, targetNextValidatorsHash)
     function verifyTendermintProof(
         bytes calldata proof,
         bytes calldata publicValues
     ) public {
-        (
-            uint64 trustedHeight,
-            uint64 targetHeight,
-            bytes32 trustedHeaderHash,
-            bytes32 targetHeaderHash
-        ) = abi.decode(publicValues, (uint64, uint64, bytes32, bytes32));
+        if (frozen) {
+            revert ClientFrozen();
+        }
+
+        TendermintOutput memory output = abi.decode(
+            publicValues,
+            (TendermintOutput)
+        );
 
         // If the inputs to the proof don't match the latest header in the contract, don't update
         // the contract state.
         if (
-            trustedHeaderHash != latestHeader || trustedHeight != latestHeight
+            output.trustedHeaderHash != latestHeader ||
+            output.trustedHeight != latestHeight
         ) {
             revert InvalidTrustedHeader();
         }
 
+        // A proof of another chain, e.g. a testnet with the same validators, must not be accepted.
+        if (output.chainIdHash != chainIdHash) {
+            revert InvalidChainId();
+        }
+
+        // The prover chooses the options the headers are verified with, so a proof with a lower
+        // trust threshold or a longer trusting period than the contract's must not be accepted.
+        checkTrustOptions(
+            output.trustThresholdNumerator,
+            output.trustThresholdDenominator,
+            output.trustingPeriod,
+            output.clockDrift
+        );
+
         // Verify the proof with the associated public values.
         verifier.verifyProof(tendermintProgramVkeyHash, publicValues, proof);
 
         // Update the latest header and height to the new values.
-        latestHeader = targetHeaderHash;
-        latestHeight = targetHeight;
+        latestHeader = output.targetHeaderHash;
+        latestHeight = output.targetHeight;
+    }
+
+    // @notice Freeze the light client with an SP1 proof of misbehaviour, i.e. a proof that two
+    // conflicting headers at the same height are both sufficiently signed by the validators of a
+    // common header, which verifies along a path from the latest trusted header.
+    // @param proof The proof to verified. Should correspond to the supplied `publicValues`.
+    // @param publicValues The public values to verify the proof against. The `publicValues` is the
+    // ABI-encoded tuple: (tag, trustedHeight, trustedHeaderHash, conflictHeight,
+    // conflictingHeaderHash1, conflictingHeaderHash2, trustThresholdNumerator,
+    // trustThresholdDenominator, trustingPeriod, clockDrift, chainIdHash)
+    function submitMisbehaviourProof(
+        bytes calldata proof,
+        bytes calldata publicValues
+    ) public {
+        if (frozen) {
+            revert ClientFrozen();
+        }
+
+        MisbehaviourOutput memory output = abi.decode(
+            publicValues,
+            (MisbehaviourOutput)
+        );
+
+        if (
+            output.tag != MISBEHAVIOUR_TAG ||
+            output.conflictingHeaderHash1 == output.conflictingHeaderHash2
+        ) {
+            revert InvalidMisbehaviour();
+        }
+
+        // The conflicting headers must descend from the header the contract currently trusts.
+        if (
+            output.trustedHeaderHash != latestHeader ||
+            output.trustedHeight != latestHeight
+        ) {
+            revert InvalidTrustedHeader();
+        }
+
+        // Freezing is permanent, so the conflicting headers must be of the contract's chain and
+        // verified with its options, not e.g. with a lower trust threshold and keys leaked by an
+        // old validator set.
+        if (output.chainIdHash != chainIdHash) {
+            revert InvalidChainId();
+        }
+        checkTrustOptions(
+            output.trustThresholdNumerator,
+            output.trustThresholdDenominator,
+            output.trustingPeriod,
+            output.clockDrift
+        );
+
+        // Verify the proof with the associated public values.
+        verifier.verifyProof(tendermintProgramVkeyHash, publicValues, proof);
+
+        frozen = true;
+    }
+
+    // @notice Revert unless the light client options a proof was generated with are the contract's.
+    function checkTrustOptions(
+        uint64 _trustThresholdNumerator,
+        uint64 _trustThresholdDenominator,
+        uint64 _trustingPeriod,
+        uint64 _clockDrift
+    ) internal view {
+        if (
+            _trustThresholdNumerator != trustThresholdNumerator ||
+            _trustThresholdDenominator != trustThresholdDenominator ||
+            _trustingPeriod != trustingPeriod ||
+            _clockDrift != clockDrift
+        ) {
+            revert InvalidTrustOptions();
+        }
     }
 }
@@ -13,6 +13,11 @@ struct SP1TendermintFixtureJson {
     bytes32 targetHeaderHash;
     uint64 trustedHeight;
     uint64 targetHeight;
+    bytes32 chainIdHash;
+    uint64 trustThresholdNumerator;
+    uint64 trustThresholdDenominator;
+    uint64 trustingPeriod;
+    uint64 clockDrift;
     bytes32 vkey;
     bytes publicValues;
     bytes proof;
@@ -31,6 +36,11 @@ contract SP1TendermintTest is Test {
             fixture.vkey,
             fixture.trustedHeaderHash,
             fixture.trustedHeight,
+            fixture.chainIdHash,
+            fixture.trustThresholdNumerator,
+            fixture.trustThresholdDenominator,
+            fixture.trustingPeriod,
+            fixture.clockDrift,
             address(verifier)
         );
 
@@ -42,6 +52,11 @@ contract SP1TendermintTest is Test {
             mockFixture.vkey,
             mockFixture.trustedHeaderHash,
             mockFixture.trustedHeight,
+            mockFixture.chainIdHash,
+            mockFixture.trustThresholdNumerator,
+            mockFixture.trustThresholdDenominator,
+            mockFixture.trustingPeriod,
+            mockFixture.clockDrift,
             address(mockVerifier)
         );
     }
@@ -56,6 +71,15 @@ contract SP1TendermintTest is Test {
         bytes32 targetHeaderHash = json.readBytes32(".targetHeaderHash");
         uint64 trustedHeight = uint64(json.readUint(".trustedHeight"));
         uint64 targetHeight = uint64(json.readUint(".targetHeight"));
+        bytes32 chainIdHash = json.readBytes32(".chainIdHash");
+        uint64 trustThresholdNumerator = uint64(
+            json.readUint(".trustThresholdNumerator")
+        );
+        uint64 trustThresholdDenominator = uint64(
+            json.readUint(".trustThresholdDenominator")
+        );
+        uint64 trustingPeriod = uint64(json.readUint(".trustingPeriod"));
+        uint64 clockDrift = uint64(json.readUint(".clockDrift"));
         bytes32 vkey = json.readBytes32(".vkey");
         bytes memory publicValues = json.readBytes(".publicValues");
         bytes memory proof = json.readBytes(".proof");
@@ -65,6 +89,11 @@ contract SP1TendermintTest is Test {
             targetHeaderHash: targetHeaderHash,
             trustedHeight: trustedHeight,
             targetHeight: targetHeight,
+            chainIdHash: chainIdHash,
+            trustThresholdNumerator: trustThresholdNumerator,
+            trustThresholdDenominator: trustThresholdDenominator,
+            trustingPeriod: trustingPeriod,
+            clockDrift: clockDrift,
             vkey: vkey,
             publicValues: publicValues,
             proof: proof
@@ -111,4 +140,234 @@ contract SP1TendermintTest is Test {
         assert(mockTendermint.latestHeader() == fixture.targetHeaderHash);
         assert(mockTendermint.latestHeight() == fixture.targetHeight);
     }
+
+    function mockUpdatePublicValues(
+        uint64 trustThresholdNumerator,
+        uint64 trustThresholdDenominator,
+        uint64 trustingPeriod,
+        uint64 clockDrift
+    ) internal view returns (bytes memory) {
+        return
+            mockUpdatePublicValues(
+                trustThresholdNumerator,
+                trustThresholdDenominator,
+                trustingPeriod,
+                clockDrift,
+                mockTendermint.chainIdHash()
+            );
+    }
+
+    function mockUpdatePublicValues(
+        uint64 trustThresholdNumerator,
+        uint64 trustThresholdDenominator,
+        uint64 trustingPeriod,
+        uint64 clockDrift,
+        bytes32 chainIdHash
+    ) internal view returns (bytes memory) {
+        return
+            abi.encode(
+                SP1Tendermint.TendermintOutput({
+                    trustedHeight: mockTendermint.latestHeight(),
+                    targetHeight: mockTendermint.latestHeight() + 1,
+                    trustedHeaderHash: mockTendermint.latestHeader(),
+                    targetHeaderHash: bytes32(uint256(1)),
+                    trustThresholdNumerator: trustThresholdNumerator,
+                    trustThresholdDenominator: trustThresholdDenominator,
+                    trustingPeriod: trustingPeriod,
+                    clockDrift: clockDrift,
+                    chainIdHash: chainIdHash,
+                    targetAppHash: hex"01",
+                    targetValidatorsHash: bytes32(0),
+                    targetNextValidatorsHash: bytes32(0)
+                })
+            );
+    }
+
+    // Confirm that an update verified with the contract's light client options is accepted.
+    function test_MockUpdateWithTrustOptions() public {
+        bytes memory publicValues = mockUpdatePublicValues(
+            mockTendermint.trustThresholdNumerator(),
+            mockTendermint.trustThresholdDenominator(),
+            mockTendermint.trustingPeriod(),
+            mockTendermint.clockDrift()
+        );
+        uint64 targetHeight = mockTendermint.latestHeight() + 1;
+
+        mockTendermint.verifyTendermintProof(bytes(""), publicValues);
+
+        assert(mockTendermint.latestHeader() == bytes32(uint256(1)));
+        assert(mockTendermint.latestHeight() == targetHeight);
+    }
+
+    // Confirm that an update verified with a lower trust threshold than the contract's is rejected.
+    function testRevert_MismatchedTrustThreshold() public {
+        bytes memory publicValues = mockUpdatePublicValues(
+            mockTendermint.trustThresholdNumerator() - 1,
+            mockTendermint.trustThresholdDenominator(),
+            mockTendermint.trustingPeriod(),
+            mockTendermint.clockDrift()
+        );
+
+        vm.expectRevert(SP1Tendermint.InvalidTrustOptions.selector);
+        mockTendermint.verifyTendermintProof(bytes(""), publicValues);
+    }
+
+    // Confirm that an update verified with a longer trusting period or clock drift than the
+    // contract's is rejected.
+    function testRevert_MismatchedTrustingPeriod() public {
+        bytes memory publicValues = mockUpdatePublicValues(
+            mockTendermint.trustThresholdNumerator(),
+            mockTendermint.trustThresholdDenominator(),
+            mockTendermint.trustingPeriod() * 100,
+            mockTendermint.clockDrift()
+        );
+
+        vm.expectRevert(SP1Tendermint.InvalidTrustOptions.selector);
+        mockTendermint.verifyTendermintProof(bytes(""), publicValues);
+
+        publicValues = mockUpdatePublicValues(
+            mockTendermint.trustThresholdNumerator(),
+            mockTendermint.trustThresholdDenominator(),
+            mockTendermint.trustingPeriod(),
+            mockTendermint.clockDrift() + 1 days
+        );
+
+        vm.expectRevert(SP1Tendermint.InvalidTrustOptions.selector);
+        mockTendermint.verifyTendermintProof(bytes(""), publicValues);
+    }
+
+    // Confirm that an update of another Tendermint chain is rejected.
+    function testRevert_MismatchedChainId() public {
+        bytes memory publicValues = mockUpdatePublicValues(
+            mockTendermint.trustThresholdNumerator(),
+            mockTendermint.trustThresholdDenominator(),
+            mockTendermint.trustingPeriod(),
+            mockTendermint.clockDrift(),
+            sha256("another-chain")
+        );
+
+        vm.expectRevert(SP1Tendermint.InvalidChainId.selector);
+        mockTendermint.verifyTendermintProof(bytes(""), publicValues);
+    }
+
+    function mockMisbehaviourPublicValues(
+        bytes32 tag,
+        bytes32 conflictingHeaderHash2
+    ) internal view returns (bytes memory) {
+        return
+            mockMisbehaviourPublicValues(
+                tag,
+                conflictingHeaderHash2,
+                mockTendermint.trustThresholdNumerator(),
+                mockTendermint.trustingPeriod(),
+                mockTendermint.chainIdHash()
+            );
+    }
+
+    function mockMisbehaviourPublicValues(
+        bytes32 tag,
+        bytes32 conflictingHeaderHash2,
+        uint64 trustThresholdNumerator,
+        uint64 trustingPeriod,
+        bytes32 chainIdHash
+    ) internal view returns (bytes memory) {
+        return
+            abi.encode(
+                tag,
+                mockTendermint.latestHeight(),
+                mockTendermint.latestHeader(),
+                mockTendermint.latestHeight() + 1,
+                bytes32(uint256(1)),
+                conflictingHeaderHash2,
+                trustThresholdNumerator,
+                mockTendermint.trustThresholdDenominator(),
+                trustingPeriod,
+                mockTendermint.clockDrift(),
+                chainIdHash
+            );
+    }
+
+    // Confirm that a proof of misbehaviour freezes the light client.
+    function test_MisbehaviourFreezesClient() public {
+        SP1TendermintFixtureJson memory fixture = loadFixture(
+            "mock_fixture.json"
+        );
+        bytes memory publicValues = mockMisbehaviourPublicValues(
+            mockTendermint.MISBEHAVIOUR_TAG(),
+            bytes32(uint256(2))
+        );
+
+        mockTendermint.submitMisbehaviourProof(bytes(""), publicValues);
+        assert(mockTendermint.frozen());
+
+        vm.expectRevert(SP1Tendermint.ClientFrozen.selector);
+        mockTendermint.verifyTendermintProof(bytes(""), fixture.publicValues);
+    }
+
+    // Confirm that the public values of an update are not accepted as misbehaviour.
+    function testRevert_UpdateAsMisbehaviour() public {
+        bytes memory publicValues = mockUpdatePublicValues(
+            mockTendermint.trustThresholdNumerator(),
+            mockTendermint.trustThresholdDenominator(),
+            mockTendermint.trustingPeriod(),
+            mockTendermint.clockDrift()
+        );
+
+        vm.expectRevert(SP1Tendermint.InvalidMisbehaviour.selector);
+        mockTendermint.submitMisbehaviourProof(bytes(""), publicValues);
+    }
+
+    // Confirm that identical headers are not accepted as misbehaviour.
+    function testRevert_NonConflictingMisbehaviour() public {
+        bytes memory publicValues = mockMisbehaviourPublicValues(
+            mockTendermint.MISBEHAVIOUR_TAG(),
+            bytes32(uint256(1))
+        );
+
+        vm.expectRevert(SP1Tendermint.InvalidMisbehaviour.selector);
+        mockTendermint.submitMisbehaviourProof(bytes(""), publicValues);
+    }
+
+    // Confirm that misbehaviour verified with a lower trust threshold or a longer trusting period
+    // than the contract's does not freeze the light client.
+    function testRevert_MisbehaviourWithMismatchedTrustOptions() public {
+        bytes32 tag = mockTendermint.MISBEHAVIOUR_TAG();
+        bytes memory publicValues = mockMisbehaviourPublicValues(
+            tag,
+            bytes32(uint256(2)),
+            mockTendermint.trustThresholdNumerator() - 1,
+            mockTendermint.trustingPeriod(),
+            mockTendermint.chainIdHash()
+        );
+
+        vm.expectRevert(SP1Tendermint.InvalidTrustOptions.selector);
+        mockTendermint.submitMisbehaviourProof(bytes(""), publicValues);
+
+        publicValues = mockMisbehaviourPublicValues(
+            tag,
+            bytes32(uint256(2)),
+            mockTendermint.trustThresholdNumerator(),
+            mockTendermint.trustingPeriod() * 100,
+            mockTendermint.chainIdHash()
+        );
+
+        vm.expectRevert(SP1Tendermint.InvalidTrustOptions.selector);
+        mockTendermint.submitMisbehaviourProof(bytes(""), publicValues);
+        assert(!mockTendermint.frozen());
+    }
+
+    // Confirm that misbehaviour on another chain does not freeze the light client.
+    function testRevert_MisbehaviourOfAnotherChain() public {
+        bytes memory publicValues = mockMisbehaviourPublicValues(
+            mockTendermint.MISBEHAVIOUR_TAG(),
+            bytes32(uint256(2)),
+            mockTendermint.trustThresholdNumerator(),
+            mockTendermint.trustingPeriod(),
+            sha256("another-chain")
+        );
+
+        vm.expectRevert(SP1Tendermint.InvalidChainId.selector);
+        mockTendermint.submitMisbehaviourProof(bytes(""), publicValues);
+        assert(!mockTendermint.frozen());
+    }
 }
@@ -1,9 +1,12 @@
-use alloy_sol_types::{sol, SolType};
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use serde::{Deserialize, Serialize};
 use sp1_sdk::{utils::setup_logger, HashableKey};
-use std::{env, path::PathBuf};
-use tendermint_operator::{util::TendermintRPCClient, TendermintProver};
+use std::path::PathBuf;
+use tendermint_light_client_verifier::{options::Options, Verdict};
+use tendermint_operator::{
+    bindings::TendermintOutput, options::TrustOptionsArgs, util::TendermintRPCClient,
+    verify::verify_natively, ProofMode, ProverKind, TendermintProver,
+};
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
@@ -19,11 +22,19 @@ struct FixtureArgs {
     /// Fixture path.
     #[clap(long, default_value = "../contracts/fixtures")]
     fixture_path: String,
-}
 
-type TendermintProofOutput = sol! {
-    tuple(uint64, uint64, bytes32, bytes32)
-};
+    /// The type of proof to generate. Only plonk and groth16 proofs can be verified by the forge
+    /// tests, so core and compressed proofs are written to a separate fixture file.
+    #[clap(long, env, value_enum, default_value_t = ProofMode::Plonk)]
+    proof_mode: ProofMode,
+
+    /// The SP1 prover that generates the proof.
+    #[clap(long, env = "SP1_PROVER", value_enum, default_value_t)]
+    prover: ProverKind,
+
+    #[clap(flatten)]
+    trust_options: TrustOptionsArgs,
+}
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -32,8 +43,20 @@ struct TendermintFixture {
     target_header_hash: String,
     trusted_height: u64,
     target_height: u64,
+    trust_threshold_numerator: u64,
+    trust_threshold_denominator: u64,
+    trusting_period: u64,
+    clock_drift: u64,
+    chain_id: String,
+    chain_id_hash: String,
+    target_app_hash: String,
+    target_validators_hash: String,
+    target_next_validators_hash: String,
     vkey: String,
     public_values: String,
+    proof_mode: ProofMode,
+    /// The proof bytes the SP1 verifier takes for plonk and groth16 proofs, or the
+    /// bincode-serialized `SP1Proof` for core and compressed proofs.
     proof: String,
 }
 
@@ -42,7 +65,8 @@ struct TendermintFixture {
 /// ```
 /// RUST_LOG=info cargo run --bin fixture --release -- --trusted-block=1 --target-block=5
 /// ```
-/// The fixture will be written to the path: ./contracts/fixtures/fixture.json
+/// The fixture will be written to the path: ./contracts/fixtures/fixture.json, or to
+/// ./contracts/fixtures/core_fixture.json for a core proof.
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     dotenv::dotenv().ok();
@@ -50,50 +74,85 @@ async fn main() -> anyhow::Result<()> {
 
     let args = FixtureArgs::parse();
 
-    let tendermint_rpc_client = TendermintRPCClient::default();
+    let tendermint_rpc_client = TendermintRPCClient::from_env()?;
 
     let (trusted_light_block, target_light_block) = tendermint_rpc_client
         .get_light_blocks(args.trusted_block, args.target_block)
-        .await;
+        .await?;
+
+    let options = Options::from(args.trust_options);
+
+    // Check that the update verifies before spending time generating a proof.
+    match verify_natively(&trusted_light_block, &target_light_block, &options)? {
+        Verdict::Success => {}
+        v => anyhow::bail!(
+            "Block {} does not verify against block {}: {:?}",
+            args.target_block,
+            args.trusted_block,
+            v
+        ),
+    }
 
-    let tendermint_prover = TendermintProver::new();
+    let tendermint_prover = TendermintProver::new(args.proof_mode, args.prover);
 
     // Generate a header update proof for the specified blocks.
-    let proof_data =
-        tendermint_prover.generate_tendermint_proof(&trusted_light_block, &target_light_block);
+    let proof_data = tendermint_prover.generate_tendermint_proof(
+        &trusted_light_block,
+        &target_light_block,
+        &options,
+    );
 
-    let bytes = proof_data.public_values.as_slice();
-    let (trusted_height, target_height, trusted_header_hash, target_header_hash) =
-        TendermintProofOutput::abi_decode(bytes, false).unwrap();
+    let output = TendermintOutput::from_public_values(proof_data.public_values.as_slice())?;
 
     let fixture = TendermintFixture {
-        trusted_header_hash: hex::encode(trusted_header_hash),
-        target_header_hash: hex::encode(target_header_hash),
-        trusted_height,
-        target_height,
+        trusted_header_hash: hex::encode(output.trustedHeaderHash),
+        target_header_hash: hex::encode(output.targetHeaderHash),
+        trusted_height: output.trustedHeight,
+        target_height: output.targetHeight,
+        trust_threshold_numerator: output.trustThresholdNumerator,
+        trust_threshold_denominator: output.trustThresholdDenominator,
+        trusting_period: output.trustingPeriod,
+        clock_drift: output.clockDrift,
+        chain_id: target_light_block.signed_header.header.chain_id.to_string(),
+        chain_id_hash: hex::encode(output.chainIdHash),
+        target_app_hash: hex::encode(&output.targetAppHash),
+        target_validators_hash: hex::encode(output.targetValidatorsHash),
+        target_next_validators_hash: hex::encode(output.targetNextValidatorsHash),
         vkey: tendermint_prover.vkey.bytes32(),
         public_values: proof_data.public_values.raw(),
-        proof: hex::encode(proof_data.bytes()),
+        proof_mode: args.proof_mode,
+        proof: if args.proof_mode.is_onchain_verifiable() {
+            hex::encode(proof_data.bytes())
+        } else {
+            hex::encode(bincode::serialize(&proof_data.proof)?)
+        },
     };
 
     // Save the proof data to the file path.
     let fixture_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(args.fixture_path);
 
-    // TODO: Change to prover.id
-    let sp1_prover_type = env::var("SP1_PROVER");
-    if sp1_prover_type.as_deref() == Ok("mock") {
-        std::fs::write(
-            fixture_path.join("mock_fixture.json"),
-            serde_json::to_string_pretty(&fixture).unwrap(),
-        )
-        .unwrap();
-    } else {
-        std::fs::write(
-            fixture_path.join("fixture.json"),
-            serde_json::to_string_pretty(&fixture).unwrap(),
-        )
-        .unwrap();
-    }
+    std::fs::write(
+        fixture_path.join(fixture_file(args.proof_mode, args.prover)),
+        serde_json::to_string_pretty(&fixture).unwrap(),
+    )
+    .unwrap();
 
     Ok(())
 }
+
+/// The name of the fixture file for proofs of the mode from the prover. The forge tests read the
+/// plonk or groth16 fixture and its mock, so core and compressed proofs, which can't be verified
+/// onchain, are written to a file named after their mode.
+fn fixture_file(proof_mode: ProofMode, prover: ProverKind) -> String {
+    let prefix = if prover == ProverKind::Mock {
+        "mock_"
+    } else {
+        ""
+    };
+    if proof_mode.is_onchain_verifiable() {
+        format!("{}fixture.json", prefix)
+    } else {
+        let mode = proof_mode.to_possible_value().unwrap();
+        format!("{}{}_fixture.json", prefix, mode.get_name())
+    }
+}
@@ -1,6 +1,10 @@
 use clap::Parser;
 use sp1_sdk::{utils::setup_logger, CpuProver, HashableKey, Prover};
-use tendermint_operator::{util::TendermintRPCClient, TENDERMINT_ELF};
+use tendermint_operator::{
+    options::TrustOptionsArgs,
+    util::{chain_id_hash, TendermintRPCClient},
+    TENDERMINT_ELF,
+};
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
@@ -8,9 +12,14 @@ struct GenesisArgs {
     /// Trusted block.
     #[clap(long)]
     trusted_block: Option<u64>,
+
+    /// Light client options the contract requires proofs to have verified headers with.
+    #[clap(flatten)]
+    trust_options: TrustOptionsArgs,
 }
 
-/// Fetches the trusted header hash for the given block height. Defaults to the latest block height.
+/// Fetches the trusted header hash for the given block height, and prints it with the light client
+/// options to deploy the contract with. Defaults to the latest block height.
 /// Example:
 /// ```
 /// RUST_LOG=info cargo run --bin genesis --release
@@ -26,24 +35,30 @@ async fn main() -> anyhow::Result<()> {
     // Generate the vkey hash to use in the contract.
     let prover = CpuProver::mock();
     let (_, vk) = prover.setup(TENDERMINT_ELF);
-    let tendermint_client = TendermintRPCClient::default();
+    let tendermint_client = TendermintRPCClient::from_env()?;
 
-    let (trusted_height, trusted_header_hash) = if let Some(trusted_block) = args.trusted_block {
-        let commit = tendermint_client.get_commit(trusted_block).await?;
-        (trusted_block, commit.result.signed_header.header.hash())
+    let commit = if let Some(trusted_block) = args.trusted_block {
+        tendermint_client.get_commit(trusted_block).await?
     } else {
-        let latest_commit = tendermint_client.get_latest_commit().await?;
-        (
-            latest_commit.result.signed_header.header.height.value(),
-            latest_commit.result.signed_header.header.hash(),
-        )
+        tendermint_client.get_latest_commit().await?
     };
+    let trusted_header = &commit.result.signed_header.header;
+    let trusted_height = trusted_header.height.value();
+    let trusted_header_hash = trusted_header.hash();
+    // The contract only accepts proofs of the chain with this chain ID.
+    let chain_id_hash = hex::encode(chain_id_hash(&trusted_header.chain_id));
 
+    let trust_options = args.trust_options;
     println!(
-        "TENDERMINT_VKEY_HASH={} TRUSTED_HEIGHT={} TRUSTED_HEADER_HASH={}",
+        "TENDERMINT_VKEY_HASH={} TRUSTED_HEIGHT={} TRUSTED_HEADER_HASH={} CHAIN_ID_HASH=0x{} TRUST_THRESHOLD_NUMERATOR={} TRUST_THRESHOLD_DENOMINATOR={} TRUSTING_PERIOD={} CLOCK_DRIFT={}",
         vk.bytes32(),
         trusted_height,
-        trusted_header_hash
+        trusted_header_hash,
+        chain_id_hash,
+        trust_options.trust_threshold.numerator(),
+        trust_options.trust_threshold.denominator(),
+        trust_options.trusting_period,
+        trust_options.clock_drift
     );
 
     Ok(())
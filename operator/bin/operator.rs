@@ -1,77 +1,919 @@
-use alloy_primitives::U256;
-use alloy_sol_types::{sol, SolCall, SolValue};
-use log::{debug, info};
-use sp1_sdk::utils::setup_logger;
-use std::time::Duration;
-use tendermint_operator::{contract::ContractClient, util::TendermintRPCClient, TendermintProver};
-
-sol! {
-    contract SP1Tendermint {
-        bytes32 public latestHeader;
-        uint64 public latestHeight;
-
-        function verifyTendermintProof(
-            bytes calldata proof,
-            bytes calldata publicValues
-        ) public;
-    }
+use alloy::network::EthereumWallet;
+use alloy_primitives::{Address, B256};
+use anyhow::{anyhow, Context};
+use clap::{Parser, Subcommand};
+use log::{debug, error, info, warn};
+use sp1_sdk::{utils::setup_logger, HashableKey, SP1ProofWithPublicValues};
+use std::{
+    collections::BTreeMap,
+    env,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tendermint_light_client_verifier::{options::Options, types::LightBlock, Verdict};
+use tendermint_operator::{
+    bindings::{SP1TendermintClient, TendermintOutput},
+    bisection::plan_light_block_path,
+    config::{ConfigOverrides, OperatorConfig},
+    contract::{ContractError, RevertReason, Simulation},
+    evidence::LightClientAttackEvidence,
+    metrics::OperatorMetrics,
+    relay::{group_by_trusted_state, TrustedState},
+    schedule::ScheduleArgs,
+    signer::{SignerConfig, WatchOnlySigner},
+    state::{CompletedProof, StateStore},
+    util::{chain_id_hash, Divergence, TendermintRPCClient, WitnessError},
+    verify::verify_natively,
+    watchdog::{ExpiryStatus, ExpiryWatchdog, ExpiryWatchdogArgs},
+    ProverKind, TendermintProver,
+};
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct OperatorArgs {
+    /// TOML file the operator reads its configuration from. Environment variables and the flags
+    /// below override its settings, and the defaults are used if unset.
+    #[clap(long, env = "OPERATOR_CONFIG")]
+    config: Option<PathBuf>,
+
+    /// Prove a single update and simulate relaying it to the contract with `eth_call` and
+    /// `eth_estimateGas`, reporting whether it would succeed, without sending a transaction.
+    #[clap(long)]
+    dry_run: bool,
+
+    /// The address a dry run simulates relaying from. Defaults to the address of the configured
+    /// key, which is resolved without decrypting a keystore.
+    #[clap(long, requires = "dry_run")]
+    from: Option<Address>,
+
+    /// Run even if the contract's Tendermint program vkey hash differs from the local program's,
+    /// e.g. while migrating the contract to a new program.
+    #[clap(long, env)]
+    allow_vkey_mismatch: bool,
+
+    #[clap(flatten)]
+    overrides: ConfigOverrides,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
 }
 
-/// An implementation of a Tendermint Light Client operator that will poll an onchain Tendermint
-/// light client and generate a proof of the transition from the latest block in the contract to the
-/// latest block on the chain. Then, submits the proof to the contract and updates the contract with
-/// the latest block hash and height.
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Execute the Tendermint program for an update without generating a proof, and report the
+    /// cycle count, the syscall counts and the public values.
+    Execute {
+        /// Trusted block.
+        #[clap(long)]
+        trusted_block: u64,
+
+        /// Target block.
+        #[clap(long)]
+        target_block: u64,
+    },
+}
+
+/// An implementation of a Tendermint Light Client operator that will poll onchain Tendermint light
+/// clients and generate a proof of the transition from the latest block in the contracts to the
+/// latest block on the chain. Then, submits the proof to the contracts and updates them with the
+/// latest block hash and height. Contracts at the same latest block share a single proof.
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     dotenv::dotenv().ok();
     setup_logger();
 
-    // Instantiate a contract client to interact with the deployed Solidity Tendermint contract.
-    let contract_client = ContractClient::default();
+    let args = OperatorArgs::parse();
+    let mut config = OperatorConfig::load(args.config.as_deref())?;
+    config.apply_overrides(args.overrides);
+    let options = Options::from(config.trust_options.clone());
+
+    if let Some(Command::Execute {
+        trusted_block,
+        target_block,
+    }) = args.command
+    {
+        return execute(&config, trusted_block, target_block, &options).await;
+    }
+
+    config.validate()?;
+    let poll_interval = config.schedule.poll_interval();
+
+    // Instantiate a contract client for each deployed Solidity Tendermint contract, all signing
+    // with the same wallet. A dry run only simulates transactions, so it doesn't unlock the key.
+    let signer_config = SignerConfig::from_env(|name| env::var(name).ok())?;
+    let wallet = if args.dry_run {
+        let from = match args.from {
+            Some(from) => from,
+            None => signer_config.address().context(
+                "Failed to resolve the address to simulate relaying from, pass it with --from",
+            )?,
+        };
+        EthereumWallet::from(WatchOnlySigner::new(from))
+    } else {
+        signer_config.wallet()?
+    };
+    let contract_clients = config
+        .relay_targets
+        .iter()
+        .map(|target| target.client(wallet.clone(), config.fees.clone()))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let metrics = Arc::new(OperatorMetrics::new()?);
+    if let Some(metrics_addr) = config.metrics_addr {
+        info!("Serving metrics on http://{}/metrics", metrics_addr);
+        let metrics = metrics.clone();
+        tokio::spawn(async move {
+            if let Err(e) = metrics.serve(metrics_addr).await {
+                error!("Metrics server failed: {:#}", e);
+            }
+        });
+    }
 
     // Instantiate a Tendermint prover based on the environment variable.
-    let tendermint_rpc_client = TendermintRPCClient::default();
-    let prover = TendermintProver::new();
+    let tendermint_rpc_client = config
+        .tendermint_rpc_client()?
+        .with_error_counter(metrics.rpc_errors.clone());
+    let prover = TendermintProver::new(config.proof_mode, config.prover);
+
+    // Proofs of a different program than the one a contract accepts would only revert.
+    let local_vkey_hash = prover.vkey.bytes32();
+    for contract_client in &contract_clients {
+        let contract_vkey_hash = contract_client.vkey_hash().await?.to_string();
+        if contract_vkey_hash == local_vkey_hash {
+            continue;
+        }
+        if !args.allow_vkey_mismatch {
+            anyhow::bail!(
+                "The {} accepts proofs of the Tendermint program with vkey hash {}, but the local program has vkey hash {}. Rebuild the program from the source the contract was deployed with, or pass --allow-vkey-mismatch during a migration.",
+                contract_client,
+                contract_vkey_hash,
+                local_vkey_hash
+            );
+        }
+        warn!(
+            "The vkey hash {} of the {} does not match the local vkey hash {}, proofs will be rejected until the contract is migrated",
+            contract_vkey_hash, contract_client, local_vkey_hash
+        );
+    }
+
+    // Contracts reject proofs of another chain than the one they track, or that verified headers
+    // with different light client options.
+    let chain_id = tendermint_rpc_client
+        .get_latest_commit()
+        .await?
+        .result
+        .signed_header
+        .header
+        .chain_id;
+    let chain_id_hash = B256::from(chain_id_hash(&chain_id));
+    for contract_client in &contract_clients {
+        if contract_client.chain_id_hash().await? != chain_id_hash {
+            anyhow::bail!(
+                "The {} tracks a different Tendermint chain than {}",
+                contract_client,
+                chain_id
+            );
+        }
+
+        let contract_options = contract_client.trust_options().await?;
+        if contract_options != options {
+            anyhow::bail!(
+                "The {} requires proofs verified with the light client options {:?}, but the operator is configured with {:?}",
+                contract_client,
+                contract_options,
+                options
+            );
+        }
+    }
+
+    // Resume from the state persisted before the last restart.
+    let state_store = StateStore::open(&config.state_file)?;
+    info!(
+        "Loaded operator state from {}",
+        state_store.path().display()
+    );
+
+    let mut operator = Operator {
+        contract_clients,
+        tendermint_rpc_client,
+        prover,
+        state_store,
+        metrics,
+        schedule: config.schedule,
+        expiry_watchdog_args: config.expiry_watchdog,
+        expiry_watchdogs: BTreeMap::new(),
+        options,
+        report_cycles: config.report_cycles,
+    };
+
+    if args.dry_run {
+        return operator.dry_run().await;
+    }
 
     loop {
-        // Read the existing trusted header hash from the contract.
-        let contract_latest_height = SP1Tendermint::latestHeightCall {}.abi_encode();
-        let contract_latest_height = contract_client.read(contract_latest_height).await?;
-        let contract_latest_height = U256::abi_decode(&contract_latest_height, true).unwrap();
-        let trusted_block_height: u64 = contract_latest_height.try_into().unwrap();
-
-        if trusted_block_height == 0 {
-            panic!(
-                "No trusted height found on the contract. Something is wrong with the contract."
+        // Wait for the transactions sent before a restart or a failed iteration to be mined, so the
+        // contracts' latest heights reflect them.
+        if let Err(e) = operator.resolve_pending_transactions().await {
+            error!("Failed to resolve pending transactions: {:#}", e);
+            tokio::time::sleep(poll_interval).await;
+            continue;
+        }
+
+        let (groups, unread) = operator.read_trusted_states().await;
+        if operator.contract_clients.is_empty() {
+            anyhow::bail!("Every light client is frozen, so no update can ever be relayed again");
+        }
+
+        // Jobs and proofs that don't start at any contract's latest height would be rejected. They
+        // are kept while a contract couldn't be read, since they may start at its latest height.
+        if unread == 0 {
+            let heights = groups.keys().map(|state| state.height).collect::<Vec<_>>();
+            let pruned = operator.state_store.prune_stale(&heights)?;
+            if pruned > 0 {
+                info!(
+                    "Dropped {} stale jobs and proofs that don't start at any contract's latest block",
+                    pruned
+                );
+            }
+            operator
+                .expiry_watchdogs
+                .retain(|trusted_state, _| groups.contains_key(trusted_state));
+        }
+
+        // Update the contracts furthest behind first. Failures are usually transient, so log them
+        // and retry on the next iteration.
+        for (trusted_state, group) in &groups {
+            if let Err(e) = operator.update(*trusted_state, group).await {
+                error!(
+                    "Failed to update the contracts at block {}: {:#}",
+                    trusted_state.height, e
+                );
+            }
+        }
+
+        // Wake up as soon as a contract's latest block crosses the urgent expiry threshold, so
+        // its update starts right away rather than at the next poll.
+        let now = Instant::now();
+        let sleep = operator
+            .expiry_watchdogs
+            .values()
+            .filter_map(|watchdog| watchdog.urgent_at())
+            .map(|urgent_at| urgent_at.saturating_duration_since(now))
+            .fold(poll_interval, Duration::min);
+        debug!("sleeping for {} seconds", sleep.as_secs());
+        tokio::time::sleep(sleep).await;
+    }
+}
+
+/// The clients and the persisted state of the operator loop.
+struct Operator {
+    contract_clients: Vec<SP1TendermintClient>,
+    tendermint_rpc_client: TendermintRPCClient,
+    prover: TendermintProver,
+    state_store: StateStore,
+    metrics: Arc<OperatorMetrics>,
+    schedule: ScheduleArgs,
+    expiry_watchdog_args: ExpiryWatchdogArgs,
+    /// A watchdog for each group of contracts, by the trusted state they share.
+    expiry_watchdogs: BTreeMap<TrustedState, ExpiryWatchdog>,
+    options: Options,
+    /// Whether to execute each update before proving it, for its cycle count.
+    report_cycles: bool,
+}
+
+impl Operator {
+    /// Reads the latest block of every contract, and groups the contracts at the same latest block,
+    /// by the indices of their clients. Contracts frozen by a proof of misbehaviour can never be
+    /// updated again, so they are no longer relayed to. Contracts that can't be read, e.g. because
+    /// their RPC is down, are skipped until the next iteration. Returns the groups and the number of
+    /// contracts skipped.
+    async fn read_trusted_states(&mut self) -> (BTreeMap<TrustedState, Vec<usize>>, usize) {
+        let mut unread = vec![false; self.contract_clients.len()];
+        let mut i = 0;
+        while i < self.contract_clients.len() {
+            match self.contract_clients[i].frozen().await {
+                Ok(true) => {
+                    let contract_client = self.contract_clients.remove(i);
+                    unread.remove(i);
+                    error!(
+                        "The light client of the {} is frozen, no longer relaying to it",
+                        contract_client
+                    );
+                    continue;
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    self.record_read_error(i, e.into());
+                    unread[i] = true;
+                }
+            }
+            i += 1;
+        }
+
+        let mut trusted_states = vec![];
+        for (i, contract_client) in self.contract_clients.iter().enumerate() {
+            if unread[i] {
+                continue;
+            }
+            let trusted_state = match contract_client.trusted_state().await {
+                Ok(trusted_state) => trusted_state,
+                Err(e) => {
+                    self.record_read_error(i, e.into());
+                    unread[i] = true;
+                    continue;
+                }
+            };
+            if trusted_state.height == 0 {
+                self.record_read_error(
+                    i,
+                    anyhow!("No trusted height found, something is wrong with the contract"),
+                );
+                unread[i] = true;
+                continue;
+            }
+            let (chain_id, contract) = labels(contract_client);
+            self.metrics
+                .contract_latest_height
+                .with_label_values(&[&chain_id, &contract])
+                .set(trusted_state.height as i64);
+            trusted_states.push((i, trusted_state));
+        }
+
+        let unread = unread.iter().filter(|unread| **unread).count();
+        (group_by_trusted_state(trusted_states), unread)
+    }
+
+    /// Logs and counts a failed read of the state of the contract, which is skipped this iteration.
+    fn record_read_error(&self, i: usize, error: anyhow::Error) {
+        let contract_client = &self.contract_clients[i];
+        error!(
+            "Failed to read the state of the {}, skipping it until the next iteration: {:#}",
+            contract_client, error
+        );
+        let (chain_id, contract) = labels(contract_client);
+        self.metrics
+            .contract_read_errors
+            .with_label_values(&[&chain_id, &contract])
+            .inc();
+    }
+
+    /// Updates a group of contracts at the same trusted state, with a proof generated before a
+    /// restart if there is one, and otherwise with a proof of an update to a newly selected target
+    /// block.
+    async fn update(&mut self, trusted_state: TrustedState, group: &[usize]) -> anyhow::Result<()> {
+        let trusted_block_height = trusted_state.height;
+
+        // Submit a proof that was generated before a restart but never relayed.
+        if let Some(proof) = self.completed_proof(&trusted_state) {
+            info!(
+                "Resuming the submission of the proof from block {} to block {}",
+                proof.trusted_height, proof.target_height
             );
+            return self.relay_proof(group, proof).await;
         }
 
-        let chain_latest_block_height = tendermint_rpc_client.get_latest_block_height().await;
-        let (trusted_light_block, target_light_block) = tendermint_rpc_client
-            .get_light_blocks(trusted_block_height, chain_latest_block_height)
-            .await;
+        let chain_latest_block_height = self
+            .tendermint_rpc_client
+            .get_latest_block_height()
+            .await
+            .context("Failed to fetch the latest block height")?;
+        let expiry_status = match self
+            .record_lag_metrics(group, trusted_block_height, chain_latest_block_height)
+            .await
+        {
+            Ok(age) => self
+                .expiry_watchdogs
+                .entry(trusted_state)
+                .or_insert_with(|| {
+                    ExpiryWatchdog::new(&self.expiry_watchdog_args, self.options.trusting_period)
+                })
+                .check(trusted_block_height, age),
+            Err(e) => {
+                warn!(
+                    "Failed to check the age of block {}: {}",
+                    trusted_block_height, e
+                );
+                ExpiryStatus::Healthy
+            }
+        };
+
+        // Resume a proving job from the trusted block that was interrupted by a restart, otherwise
+        // select a target from the latest block.
+        let resumed_job = self
+            .state_store
+            .state()
+            .in_flight_job
+            .clone()
+            .filter(|job| job.trusted_height == trusted_block_height);
+        let target_block_height = match &resumed_job {
+            Some(job) => {
+                info!(
+                    "Resuming the proving job from block {} to block {}",
+                    job.trusted_height, job.target_height
+                );
+                job.target_height
+            }
+            // Close to the end of the trusting period, any update is worth proving.
+            None if expiry_status.is_urgent() => match self
+                .schedule
+                .select_urgent_target(trusted_block_height, chain_latest_block_height)
+            {
+                Some(height) => height,
+                None => return Ok(()),
+            },
+            None => match self
+                .schedule
+                .select_target(trusted_block_height, chain_latest_block_height)
+            {
+                Some(height) => height,
+                None => {
+                    debug!(
+                        "No target block worth proving from block {} with latest block {}",
+                        trusted_block_height, chain_latest_block_height
+                    );
+                    return Ok(());
+                }
+            },
+        };
 
-        // Generate a proof of the transition from the trusted block to the target block.
-        let proof_data =
-            prover.generate_tendermint_proof(&trusted_light_block, &target_light_block);
+        // Find a path of light blocks to the target block that verifies natively, bisecting if the
+        // trusted validators did not sign the target block with enough voting power.
+        let light_block_path = match plan_light_block_path(
+            &self.tendermint_rpc_client,
+            trusted_block_height,
+            target_block_height,
+            &self.options,
+        )
+        .await
+        {
+            Ok(path) => path,
+            Err(e) => {
+                // Don't get stuck on a resumed target, pick a new one on the next iteration.
+                if resumed_job.is_some() {
+                    self.state_store.abandon_job()?;
+                }
+                return Err(e.context(format!(
+                    "Failed to plan an update from block {} to block {}",
+                    trusted_block_height, target_block_height
+                )));
+            }
+        };
+
+        // Refuse to prove a path through blocks that the witnesses disagree with or don't confirm,
+        // since the primary RPC may be serving forked headers.
+        let divergence = match self
+            .tendermint_rpc_client
+            .check_witnesses(&light_block_path[1..])
+            .await
+        {
+            Ok(()) => None,
+            Err(WitnessError::Divergence(divergence)) => Some(divergence),
+            Err(e) => {
+                return Err(anyhow::Error::new(e).context(format!(
+                    "Refusing to prove an update to block {}",
+                    target_block_height
+                )))
+            }
+        };
+        if let Some(divergence) = divergence {
+            error!("Refusing to prove a diverging block: {}", divergence);
+            if resumed_job.is_some() {
+                self.state_store.abandon_job()?;
+            }
+
+            // If both conflicting blocks verify against a block the contracts trust, the light
+            // client is under attack, so freeze the contracts with a proof of the misbehaviour.
+            if let Err(e) = self
+                .submit_misbehaviour(group, &light_block_path, &divergence)
+                .await
+            {
+                error!("Did not submit misbehaviour: {:#}", e);
+            }
+            return Ok(());
+        }
 
-        // Construct the on-chain call and relay the proof to the contract.
-        let verify_tendermint_proof_call_data = SP1Tendermint::verifyTendermintProofCall {
-            publicValues: proof_data.public_values.to_vec().into(),
+        // Generate a proof of the transition from the trusted block to the target block. The job
+        // is recorded first, so a restart resumes it with the same target block.
+        if resumed_job.is_none() {
+            self.state_store
+                .start_job(trusted_block_height, target_block_height)?;
+        }
+        if self.report_cycles {
+            match self.prover.execute_path(&light_block_path, &self.options) {
+                Ok(execution) => self.metrics.proof_cycles.set(execution.cycles as i64),
+                Err(e) => warn!("Failed to execute the update for its cycle count: {:#}", e),
+            }
+        }
+        let proving_start = Instant::now();
+        let proof_data = if self.prover.is_network() {
+            let request_id = resumed_job.and_then(|job| job.request_id);
+            self.prove_on_network(&light_block_path, request_id).await?
+        } else {
+            self.prover
+                .generate_tendermint_proof_for_path(&light_block_path, &self.options)
+        };
+        self.metrics
+            .proof_duration_seconds
+            .observe(proving_start.elapsed().as_secs_f64());
+        let proof = CompletedProof {
+            trusted_height: trusted_block_height,
+            target_height: target_block_height,
+            public_values: proof_data.public_values.to_vec().into(),
             proof: proof_data.bytes().into(),
+        };
+        self.state_store.complete_job(proof.clone())?;
+
+        // The proof stays in the state store if relaying fails, and is retried on the next
+        // iteration.
+        self.relay_proof(group, proof).await
+    }
+
+    /// Requests a proof of the update along the path from the prover network, recording the request
+    /// in the in-flight job, and waits for it. Waits for the proof of the given request instead if
+    /// it was made before a restart.
+    async fn prove_on_network(
+        &mut self,
+        light_block_path: &[LightBlock],
+        request_id: Option<B256>,
+    ) -> anyhow::Result<SP1ProofWithPublicValues> {
+        let request_id = match request_id {
+            Some(request_id) => {
+                info!("Resuming the proof request {}", request_id);
+                request_id
+            }
+            None => {
+                let request_id = self
+                    .prover
+                    .request_tendermint_proof_for_path(light_block_path, &self.options)
+                    .await?;
+                self.state_store.record_request(request_id)?;
+                request_id
+            }
+        };
+
+        match self.prover.wait_for_proof(request_id).await {
+            Ok(proof) => Ok(proof),
+            Err(e) => {
+                // Keep waiting for the request on the next iteration unless it will never be
+                // fulfilled.
+                if TendermintProver::is_failed_request(&e) {
+                    self.state_store.clear_request()?;
+                }
+                Err(e.context(format!("Failed to prove with request {}", request_id)))
+            }
         }
-        .abi_encode();
-        contract_client
-            .send(verify_tendermint_proof_call_data)
+    }
+
+    /// The completed proof of an update from the trusted state, if there is one.
+    fn completed_proof(&self, trusted_state: &TrustedState) -> Option<CompletedProof> {
+        self.state_store
+            .state()
+            .completed_proofs
+            .iter()
+            .find(|proof| {
+                TendermintOutput::from_public_values(&proof.public_values)
+                    .is_ok_and(|output| output.trusted_state() == *trusted_state)
+            })
+            .cloned()
+    }
+
+    /// Relays a completed proof to every contract of the group, recording the transactions as
+    /// pending until they are mined. The proof is dropped once every contract accepted or rejected
+    /// it, and is otherwise retried on the next iteration after a provider error or a timeout.
+    async fn relay_proof(&mut self, group: &[usize], proof: CompletedProof) -> anyhow::Result<()> {
+        let mut relayed = true;
+        for &i in group {
+            let contract_client = &self.contract_clients[i];
+            match contract_client
+                .submit_proof(proof.proof.clone(), proof.public_values.clone())
+                .await
+            {
+                Ok(tx_hash) => {
+                    self.state_store.submit_proof(
+                        proof.trusted_height,
+                        proof.target_height,
+                        contract_client.contract_client().chain_id(),
+                        contract_client.contract_client().contract,
+                        tx_hash,
+                    )?;
+                    info!(
+                        "Sent transaction {:?} updating the {} from block {} to block {}",
+                        tx_hash, contract_client, proof.trusted_height, proof.target_height
+                    );
+                }
+                // The contract was updated since the proof was generated, so the proof can never be
+                // relayed to it. The next iteration re-reads the contract and proves from its
+                // latest block.
+                Err(ContractError::Revert(RevertReason::InvalidTrustedHeader)) => warn!(
+                    "Not relaying the proof from block {} to block {} to the {}, block {} is no longer its latest block",
+                    proof.trusted_height, proof.target_height, contract_client, proof.trusted_height
+                ),
+                // The contract rejects the proof, so relaying it again would revert again.
+                Err(ContractError::Revert(reason)) => error!(
+                    "Discarding the proof from block {} to block {}, the {} rejected it with {}",
+                    proof.trusted_height, proof.target_height, contract_client, reason
+                ),
+                Err(e) => {
+                    error!(
+                        "Failed to relay the proof to the {}: {:#}",
+                        contract_client, e
+                    );
+                    relayed = false;
+                }
+            }
+        }
+        if relayed {
+            self.state_store
+                .discard_proof(proof.trusted_height, proof.target_height)?;
+        }
+
+        self.resolve_pending_transactions().await
+    }
+
+    /// Waits for every pending transaction, or the transaction replacing it, to be mined or
+    /// dropped and removes it from the state.
+    async fn resolve_pending_transactions(&mut self) -> anyhow::Result<()> {
+        for pending_tx in self.state_store.state().pending_transactions.clone() {
+            let contract_client = self.contract_clients.iter().find(|contract_client| {
+                contract_client.contract_client().chain_id() == pending_tx.chain_id
+                    && contract_client.contract_client().contract == pending_tx.contract
+            });
+            let Some(contract_client) = contract_client else {
+                warn!(
+                    "Not waiting for transaction {} to contract {} on chain {}, which is no longer relayed to",
+                    pending_tx.tx_hash, pending_tx.contract, pending_tx.chain_id
+                );
+                self.state_store.resolve_transaction(pending_tx.tx_hash)?;
+                continue;
+            };
+
+            // The transaction stays in the state if waiting for it times out, and is waited for
+            // again on the next iteration.
+            let mut tx_hash = pending_tx.tx_hash;
+            let state_store = &mut self.state_store;
+            let receipt = contract_client
+                .contract_client()
+                .wait_for_receipt(pending_tx.tx_hash, |replaced, replacement| {
+                    tx_hash = replacement;
+                    state_store.replace_transaction(replaced, replacement)
+                })
+                .await?;
+            if let Some(receipt) = &receipt {
+                let (chain_id, contract) = labels(contract_client);
+                self.metrics
+                    .tx_gas_used
+                    .with_label_values(&[&chain_id, &contract])
+                    .set(receipt.gas_used as i64);
+            }
+            match receipt {
+                Some(receipt) if receipt.status() => info!(
+                    "Updated the latest block of the Tendermint light client of the {} from block {} to block {}.",
+                    contract_client, pending_tx.trusted_height, pending_tx.target_height
+                ),
+                Some(receipt) => error!(
+                    "Transaction {} updating the {} from block {} to block {} reverted",
+                    receipt.transaction_hash, contract_client, pending_tx.trusted_height, pending_tx.target_height
+                ),
+                None => warn!(
+                    "Transaction {} updating the {} from block {} to block {} was dropped",
+                    tx_hash, contract_client, pending_tx.trusted_height, pending_tx.target_height
+                ),
+            }
+            self.state_store.resolve_transaction(tx_hash)?;
+        }
+
+        Ok(())
+    }
+
+    /// Proves an update from each group's latest block to the target selected from the chain's
+    /// latest block, and simulates relaying it to every contract of the group with `eth_call` and
+    /// `eth_estimateGas` without sending a transaction.
+    async fn dry_run(&mut self) -> anyhow::Result<()> {
+        let (groups, unread) = self.read_trusted_states().await;
+        if unread > 0 {
+            anyhow::bail!("Failed to read the state of {} contracts", unread);
+        }
+        let chain_latest_block_height =
+            self.tendermint_rpc_client.get_latest_block_height().await?;
+        for (trusted_state, group) in &groups {
+            let trusted_block_height = trusted_state.height;
+            let Some(target_block_height) = self
+                .schedule
+                .select_target(trusted_block_height, chain_latest_block_height)
+            else {
+                println!(
+                    "No target block worth proving from block {} with latest block {}",
+                    trusted_block_height, chain_latest_block_height
+                );
+                continue;
+            };
+
+            let light_block_path = plan_light_block_path(
+                &self.tendermint_rpc_client,
+                trusted_block_height,
+                target_block_height,
+                &self.options,
+            )
             .await?;
+            let proof_data = self
+                .prover
+                .generate_tendermint_proof_for_path(&light_block_path, &self.options);
+            for &i in group {
+                let contract_client = &self.contract_clients[i];
+                match contract_client
+                    .simulate_proof(
+                        proof_data.bytes().into(),
+                        proof_data.public_values.to_vec().into(),
+                    )
+                    .await?
+                {
+                    Simulation::Success { gas_estimate } => println!(
+                        "Updating the {} from block {} to block {} would succeed, using an estimated {} gas",
+                        contract_client, trusted_block_height, target_block_height, gas_estimate
+                    ),
+                    Simulation::Revert(reason) => println!(
+                        "Updating the {} from block {} to block {} would revert with {}",
+                        contract_client, trusted_block_height, target_block_height, reason
+                    ),
+                }
+            }
+        }
 
-        info!(
-            "Updated the latest block of Tendermint light client at address {} from block {} to block {}.",
-            contract_client.contract, trusted_block_height, chain_latest_block_height
-        );
+        Ok(())
+    }
+
+    /// Records how far the contracts of a group are behind the chain, and how long until their
+    /// latest block is older than the trusting period. Returns the age of their latest block.
+    async fn record_lag_metrics(
+        &self,
+        group: &[usize],
+        trusted_block_height: u64,
+        chain_latest_block_height: u64,
+    ) -> anyhow::Result<Duration> {
+        self.metrics
+            .chain_latest_height
+            .set(chain_latest_block_height as i64);
+
+        let trusted_commit = self
+            .tendermint_rpc_client
+            .get_commit(trusted_block_height)
+            .await?;
+        let trusted_block_time = trusted_commit
+            .result
+            .signed_header
+            .header
+            .time
+            .unix_timestamp();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        let lag_seconds = now - trusted_block_time;
+
+        for &i in group {
+            let (chain_id, contract) = labels(&self.contract_clients[i]);
+            let labels = [chain_id.as_str(), contract.as_str()];
+            self.metrics
+                .lag_blocks
+                .with_label_values(&labels)
+                .set(chain_latest_block_height.saturating_sub(trusted_block_height) as i64);
+            self.metrics
+                .lag_seconds
+                .with_label_values(&labels)
+                .set(lag_seconds as f64);
+            self.metrics
+                .trusting_period_remaining_seconds
+                .with_label_values(&labels)
+                .set((self.options.trusting_period.as_secs() as i64 - lag_seconds) as f64);
+        }
+
+        Ok(Duration::from_secs(lag_seconds.max(0) as u64))
+    }
+
+    /// Collects the evidence of a light client attack for a divergence between the primary and a
+    /// witness on the path of an update, proves the misbehaviour and submits the proof to every
+    /// contract of the group. The common block is the contracts' trusted block or, if the
+    /// conflicting blocks don't verify against it, the last block of the path before the
+    /// divergence, which the witnesses confirmed. Fails without submitting if the evidence doesn't
+    /// verify, in which case one of the RPC endpoints is faulty rather than the light client being
+    /// attacked.
+    async fn submit_misbehaviour(
+        &self,
+        group: &[usize],
+        light_block_path: &[LightBlock],
+        divergence: &Divergence,
+    ) -> anyhow::Result<()> {
+        let evidence = LightClientAttackEvidence::collect(
+            &self.tendermint_rpc_client,
+            light_block_path[..1].to_vec(),
+            divergence,
+        )
+        .await?;
+        let evidence = match evidence.verify(&self.options) {
+            Ok(()) => evidence,
+            Err(e) => {
+                let common_path: Vec<LightBlock> = light_block_path
+                    .iter()
+                    .take_while(|light_block| {
+                        light_block.signed_header.header.height.value() < divergence.height
+                    })
+                    .cloned()
+                    .collect();
+                if common_path.len() < 2 {
+                    return Err(e);
+                }
+                warn!(
+                    "Conflicting blocks don't verify against the trusted block ({:#}), falling back to block {} as the common block",
+                    e,
+                    common_path.last().unwrap().signed_header.header.height
+                );
+                let evidence = LightClientAttackEvidence {
+                    common_path,
+                    ..evidence
+                };
+                evidence.verify(&self.options)?;
+                evidence
+            }
+        };
 
-        // Sleep for 60 seconds.
-        debug!("sleeping for 60 seconds");
-        tokio::time::sleep(Duration::from_secs(60)).await;
+        let proof_data = self
+            .prover
+            .generate_misbehaviour_proof(&evidence, &self.options);
+        for &i in group {
+            let contract_client = &self.contract_clients[i];
+            let result = async {
+                let tx_hash = contract_client
+                    .submit_misbehaviour_proof(
+                        proof_data.bytes().into(),
+                        proof_data.public_values.to_vec().into(),
+                    )
+                    .await?;
+                contract_client
+                    .contract_client()
+                    .wait_for_receipt(tx_hash, |_, _| Ok(()))
+                    .await
+            }
+            .await;
+            match result {
+                Ok(_) => info!(
+                    "Froze the Tendermint light client of the {} with a proof of misbehaviour at block {}.",
+                    contract_client, divergence.height
+                ),
+                Err(e) => error!(
+                    "Failed to submit the proof of misbehaviour to the {}: {:#}",
+                    contract_client, e
+                ),
+            }
+        }
+
+        Ok(())
     }
 }
+
+/// The values of the `chain_id` and `contract` labels of the metrics of the contract.
+fn labels(contract_client: &SP1TendermintClient) -> (String, String) {
+    (
+        contract_client.contract_client().chain_id().to_string(),
+        contract_client.contract_client().contract.to_string(),
+    )
+}
+
+/// Executes the Tendermint program for an update from the trusted block to the target block and
+/// prints the execution report.
+/// Example:
+/// ```
+/// RUST_LOG=info cargo run --bin operator --release -- execute --trusted-block=1 --target-block=5
+/// ```
+async fn execute(
+    config: &OperatorConfig,
+    trusted_block: u64,
+    target_block: u64,
+    options: &Options,
+) -> anyhow::Result<()> {
+    let tendermint_rpc_client = config.tendermint_rpc_client()?;
+    let (trusted_light_block, target_light_block) = tendermint_rpc_client
+        .get_light_blocks(trusted_block, target_block)
+        .await?;
+
+    // Check that the update verifies before executing it, since the program panics otherwise.
+    match verify_natively(&trusted_light_block, &target_light_block, options)? {
+        Verdict::Success => {}
+        v => anyhow::bail!(
+            "Block {} does not verify against block {}: {:?}",
+            target_block,
+            trusted_block,
+            v
+        ),
+    }
+
+    // Executing doesn't generate a proof, so the mock prover spares setting up the configured one.
+    let prover = TendermintProver::new(config.proof_mode, ProverKind::Mock);
+    let execution = prover.execute(&trusted_light_block, &target_light_block, options)?;
+
+    println!("Cycles: {}", execution.cycles);
+    println!("Syscalls:");
+    for (syscall, count) in &execution.syscall_counts {
+        println!("  {}: {}", syscall, count);
+    }
+    println!("Public values: {:#?}", execution.output);
+
+    Ok(())
+}
@@ -0,0 +1,260 @@
+use crate::{
+    contract::{ContractClient, ContractError, Simulation},
+    relay::TrustedState,
+};
+use alloy_primitives::{Address, Bytes, TxHash, B256};
+use alloy_sol_types::{sol, SolCall, SolType};
+use anyhow::anyhow;
+use std::{fmt, time::Duration};
+use tendermint_light_client_verifier::{options::Options, types::TrustThreshold};
+
+sol! {
+    /// The public values committed by the Tendermint program.
+    #[derive(Debug, PartialEq, Eq)]
+    struct TendermintOutput {
+        uint64 trustedHeight;
+        uint64 targetHeight;
+        bytes32 trustedHeaderHash;
+        bytes32 targetHeaderHash;
+        uint64 trustThresholdNumerator;
+        uint64 trustThresholdDenominator;
+        uint64 trustingPeriod;
+        uint64 clockDrift;
+        bytes32 chainIdHash;
+        bytes targetAppHash;
+        bytes32 targetValidatorsHash;
+        bytes32 targetNextValidatorsHash;
+    }
+
+    /// The public values committed by the Tendermint program for a proof of misbehaviour.
+    #[derive(Debug, PartialEq, Eq)]
+    struct MisbehaviourOutput {
+        bytes32 tag;
+        uint64 trustedHeight;
+        bytes32 trustedHeaderHash;
+        uint64 conflictHeight;
+        bytes32 conflictingHeaderHash1;
+        bytes32 conflictingHeaderHash2;
+        uint64 trustThresholdNumerator;
+        uint64 trustThresholdDenominator;
+        uint64 trustingPeriod;
+        uint64 clockDrift;
+        bytes32 chainIdHash;
+    }
+
+    /// The `SP1Tendermint` light client contract.
+    contract SP1Tendermint {
+        bytes32 public tendermintProgramVkeyHash;
+        bytes32 public latestHeader;
+        uint64 public latestHeight;
+        address public verifier;
+        bool public frozen;
+        bytes32 public chainIdHash;
+        uint64 public trustThresholdNumerator;
+        uint64 public trustThresholdDenominator;
+        uint64 public trustingPeriod;
+        uint64 public clockDrift;
+
+        error InvalidTrustedHeader();
+        error InvalidTrustOptions();
+        error InvalidChainId();
+        error InvalidMisbehaviour();
+        error ClientFrozen();
+
+        function verifyTendermintProof(
+            bytes calldata proof,
+            bytes calldata publicValues
+        ) public;
+
+        function submitMisbehaviourProof(
+            bytes calldata proof,
+            bytes calldata publicValues
+        ) public;
+    }
+
+    /// The errors of the SP1 verifier gateway and the SP1 verifiers it routes proofs to.
+    contract SP1VerifierGateway {
+        error RouteNotFound(bytes4 selector);
+        error RouteIsFrozen(bytes4 selector);
+        error WrongVerifierSelector(bytes4 received, bytes4 expected);
+        error InvalidProof();
+    }
+}
+
+impl TendermintOutput {
+    /// Decodes the public values of a proof of an update.
+    pub fn from_public_values(public_values: &[u8]) -> alloy_sol_types::Result<Self> {
+        Self::abi_decode(public_values, true)
+    }
+
+    /// The trusted state the update starts from, which a contract must be at to accept it.
+    pub fn trusted_state(&self) -> TrustedState {
+        TrustedState {
+            height: self.trustedHeight,
+            header: self.trustedHeaderHash,
+        }
+    }
+}
+
+impl MisbehaviourOutput {
+    /// Decodes the public values of a proof of misbehaviour.
+    pub fn from_public_values(public_values: &[u8]) -> alloy_sol_types::Result<Self> {
+        Self::abi_decode(public_values, true)
+    }
+}
+
+/// A typed client of a deployed `SP1Tendermint` contract.
+pub struct SP1TendermintClient {
+    contract_client: ContractClient,
+}
+
+impl fmt::Display for SP1TendermintClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.contract_client.fmt(f)
+    }
+}
+
+impl SP1TendermintClient {
+    pub fn new(contract_client: ContractClient) -> Self {
+        Self { contract_client }
+    }
+
+    /// The underlying client, e.g. to wait for the receipts of submitted proofs.
+    pub fn contract_client(&self) -> &ContractClient {
+        &self.contract_client
+    }
+
+    /// The latest block height of the light client.
+    pub async fn latest_height(&self) -> Result<u64, ContractError> {
+        Ok(self
+            .contract_client
+            .call(SP1Tendermint::latestHeightCall {})
+            .await?
+            .latestHeight)
+    }
+
+    /// The header hash of the latest block of the light client.
+    pub async fn latest_header(&self) -> Result<B256, ContractError> {
+        Ok(self
+            .contract_client
+            .call(SP1Tendermint::latestHeaderCall {})
+            .await?
+            .latestHeader)
+    }
+
+    /// The latest block height and header hash of the light client.
+    pub async fn trusted_state(&self) -> Result<TrustedState, ContractError> {
+        Ok(TrustedState {
+            height: self.latest_height().await?,
+            header: self.latest_header().await?,
+        })
+    }
+
+    /// The verification key hash of the Tendermint program the contract accepts proofs of.
+    pub async fn vkey_hash(&self) -> Result<B256, ContractError> {
+        Ok(self
+            .contract_client
+            .call(SP1Tendermint::tendermintProgramVkeyHashCall {})
+            .await?
+            .tendermintProgramVkeyHash)
+    }
+
+    /// The address of the SP1 verifier the contract verifies proofs with.
+    pub async fn verifier(&self) -> Result<Address, ContractError> {
+        Ok(self
+            .contract_client
+            .call(SP1Tendermint::verifierCall {})
+            .await?
+            .verifier)
+    }
+
+    /// The SHA-256 hash of the chain ID of the Tendermint chain the contract tracks.
+    pub async fn chain_id_hash(&self) -> Result<B256, ContractError> {
+        Ok(self
+            .contract_client
+            .call(SP1Tendermint::chainIdHashCall {})
+            .await?
+            .chainIdHash)
+    }
+
+    /// The light client options the contract requires proofs to have verified headers with.
+    pub async fn trust_options(&self) -> Result<Options, ContractError> {
+        let numerator = self
+            .contract_client
+            .call(SP1Tendermint::trustThresholdNumeratorCall {})
+            .await?
+            .trustThresholdNumerator;
+        let denominator = self
+            .contract_client
+            .call(SP1Tendermint::trustThresholdDenominatorCall {})
+            .await?
+            .trustThresholdDenominator;
+        let trusting_period = self
+            .contract_client
+            .call(SP1Tendermint::trustingPeriodCall {})
+            .await?
+            .trustingPeriod;
+        let clock_drift = self
+            .contract_client
+            .call(SP1Tendermint::clockDriftCall {})
+            .await?
+            .clockDrift;
+
+        Ok(Options {
+            trust_threshold: TrustThreshold::new(numerator, denominator)
+                .map_err(|e| anyhow!("invalid trust threshold in contract: {}", e))?,
+            trusting_period: Duration::from_secs(trusting_period),
+            clock_drift: Duration::from_secs(clock_drift),
+        })
+    }
+
+    /// Whether the light client was frozen by a proof of misbehaviour.
+    pub async fn frozen(&self) -> Result<bool, ContractError> {
+        Ok(self
+            .contract_client
+            .call(SP1Tendermint::frozenCall {})
+            .await?
+            .frozen)
+    }
+
+    /// Submits a proof of an update without waiting for it to be mined, and returns the
+    /// transaction hash.
+    pub async fn submit_proof(
+        &self,
+        proof: Bytes,
+        public_values: Bytes,
+    ) -> Result<TxHash, ContractError> {
+        let call = SP1Tendermint::verifyTendermintProofCall {
+            proof,
+            publicValues: public_values,
+        };
+        self.contract_client.submit(call.abi_encode()).await
+    }
+
+    /// Simulates submitting a proof of an update, without sending a transaction.
+    pub async fn simulate_proof(
+        &self,
+        proof: Bytes,
+        public_values: Bytes,
+    ) -> Result<Simulation, ContractError> {
+        let call = SP1Tendermint::verifyTendermintProofCall {
+            proof,
+            publicValues: public_values,
+        };
+        self.contract_client.simulate(call.abi_encode()).await
+    }
+
+    /// Submits a proof of misbehaviour without waiting for it to be mined, and returns the
+    /// transaction hash.
+    pub async fn submit_misbehaviour_proof(
+        &self,
+        proof: Bytes,
+        public_values: Bytes,
+    ) -> Result<TxHash, ContractError> {
+        let call = SP1Tendermint::submitMisbehaviourProofCall {
+            proof,
+            publicValues: public_values,
+        };
+        self.contract_client.submit(call.abi_encode()).await
+    }
+}
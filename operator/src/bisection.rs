@@ -0,0 +1,95 @@
+use crate::{
+    util::TendermintRPCClient,
+    verify::{verify_natively_at, verify_time},
+};
+use anyhow::{bail, Result};
+use log::{debug, info};
+use tendermint_light_client_verifier::{options::Options, types::LightBlock, Verdict};
+
+/// Plans a path of light blocks from the trusted block to the target block using skipping
+/// verification with bisection, as described in the Tendermint light client specification.
+///
+/// Every hop of the returned path is pre-verified natively with the same verifier as the zkVM
+/// program, so the path can be passed to `TendermintProver::generate_tendermint_proof_for_path`.
+/// If the target block verifies directly against the trusted block, the path only contains those
+/// two light blocks.
+pub async fn plan_light_block_path(
+    tendermint_rpc_client: &TendermintRPCClient,
+    trusted_block_height: u64,
+    target_block_height: u64,
+    options: &Options,
+) -> Result<Vec<LightBlock>> {
+    if target_block_height <= trusted_block_height {
+        bail!(
+            "Target block {} is not after trusted block {}",
+            target_block_height,
+            trusted_block_height
+        );
+    }
+
+    let peer_id = tendermint_rpc_client.get_peer_id().await?;
+    let trusted_light_block = tendermint_rpc_client
+        .get_light_block(trusted_block_height, peer_id)
+        .await?;
+    let target_light_block = tendermint_rpc_client
+        .get_light_block(target_block_height, peer_id)
+        .await?;
+
+    // Every hop is verified at the same time as in the zkVM program.
+    let verify_time = verify_time(&target_light_block)?;
+
+    // The verified path, starting at the trusted block, and a stack of fetched blocks that still
+    // need to be verified, with the target block at the bottom.
+    let mut path = vec![trusted_light_block];
+    let mut pending = vec![target_light_block];
+
+    while let Some(untrusted) = pending.last() {
+        let trusted = path.last().unwrap();
+        let trusted_height = trusted.signed_header.header.height.value();
+        let untrusted_height = untrusted.signed_header.header.height.value();
+
+        match verify_natively_at(trusted, untrusted, options, verify_time)? {
+            Verdict::Success => {
+                debug!(
+                    "Verified hop from block {} to block {}",
+                    trusted_height, untrusted_height
+                );
+                path.push(pending.pop().unwrap());
+            }
+            Verdict::NotEnoughTrust(tally) => {
+                let pivot_height = trusted_height + (untrusted_height - trusted_height) / 2;
+                if pivot_height == trusted_height {
+                    bail!(
+                        "Cannot bisect between block {} and block {}: {}",
+                        trusted_height,
+                        untrusted_height,
+                        tally
+                    );
+                }
+                debug!(
+                    "Not enough trust from block {} to block {} ({}), bisecting at block {}",
+                    trusted_height, untrusted_height, tally, pivot_height
+                );
+                let pivot = tendermint_rpc_client
+                    .get_light_block(pivot_height, peer_id)
+                    .await?;
+                pending.push(pivot);
+            }
+            Verdict::Invalid(e) => bail!(
+                "Block {} is invalid against block {}: {}",
+                untrusted_height,
+                trusted_height,
+                e
+            ),
+        }
+    }
+
+    info!(
+        "Planned a path of {} light blocks from block {} to block {}",
+        path.len(),
+        trusted_block_height,
+        target_block_height
+    );
+
+    Ok(path)
+}
@@ -0,0 +1,450 @@
+use crate::{
+    fees::FeeArgs,
+    options::{parse_trust_threshold, TrustOptionsArgs},
+    relay::RelayTarget,
+    schedule::{ScheduleArgs, TargetPolicy},
+    util::{split_urls, TendermintRPCClient},
+    watchdog::ExpiryWatchdogArgs,
+    ProofMode, ProverKind,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Args, ValueEnum};
+use serde::Deserialize;
+use std::{
+    env,
+    fmt::Display,
+    fs,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use tendermint_light_client_verifier::types::TrustThreshold;
+
+/// The configuration of the operator, read from a TOML file:
+///
+/// ```toml
+/// proof_mode = "plonk"
+/// tendermint_rpc_urls = ["https://rpc.celestia-mocha.com/"]
+/// metrics_addr = "0.0.0.0:9090"
+///
+/// [[relay_targets]]
+/// chain_id = 11155111
+/// contract = "0x5FbDB2315678afecb367f032d93F642f64180aa3"
+/// rpc_url = "https://ethereum-sepolia.publicnode.com/"
+///
+/// [schedule]
+/// poll_interval = 60
+/// target_policy = "aligned:100"
+/// ```
+///
+/// Every setting can be overridden by the environment variable named after it in uppercase, e.g.
+/// `POLL_INTERVAL`, except `TENDERMINT_RPC_URL`, `SP1_PROVER`, `OPERATOR_STATE_FILE` and the relay
+/// targets. Lists are comma-separated in the environment. The relay targets are overridden by
+/// `RELAY_TARGETS`, or by the single target at `CHAIN_ID`, `RPC_URL` and `CONTRACT_ADDRESS` if
+/// `CHAIN_ID` or `CONTRACT_ADDRESS` is set. The keys the operator signs with are only read from the
+/// environment. The `ConfigOverrides` command line flags take precedence over both.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OperatorConfig {
+    /// Tendermint RPC endpoints of the chain, in order of preference.
+    pub tendermint_rpc_urls: Vec<String>,
+    /// Independent Tendermint RPC endpoints that the headers of target blocks are cross-checked
+    /// against.
+    pub tendermint_witness_rpc_urls: Vec<String>,
+    /// The minimum number of witnesses that must confirm each header before it is proven, if any
+    /// witnesses are configured.
+    pub min_witness_matches: usize,
+    /// The `SP1Tendermint` contracts to relay proofs to.
+    pub relay_targets: Vec<RelayTarget>,
+    /// The type of proof to relay. Must be verifiable onchain.
+    pub proof_mode: ProofMode,
+    /// The SP1 prover that generates the proofs, overridden by `SP1_PROVER`.
+    pub prover: ProverKind,
+    pub trust_options: TrustOptionsArgs,
+    pub schedule: ScheduleArgs,
+    pub expiry_watchdog: ExpiryWatchdogArgs,
+    pub fees: FeeArgs,
+    /// Address to serve Prometheus metrics on, at `/metrics`. Metrics are not served if unset.
+    pub metrics_addr: Option<SocketAddr>,
+    /// Whether to execute each update before proving it, to report its cycle count in the
+    /// `proof_cycles` metric. The proof doesn't report its cycles, so this doubles the execution.
+    pub report_cycles: bool,
+    /// File the operator persists its in-flight jobs, unsubmitted proofs and pending transactions
+    /// to, so it can resume after a restart.
+    pub state_file: PathBuf,
+}
+
+impl Default for OperatorConfig {
+    fn default() -> Self {
+        Self {
+            tendermint_rpc_urls: vec![],
+            tendermint_witness_rpc_urls: vec![],
+            min_witness_matches: 1,
+            relay_targets: vec![],
+            proof_mode: ProofMode::Plonk,
+            prover: ProverKind::default(),
+            trust_options: TrustOptionsArgs::default(),
+            schedule: ScheduleArgs::default(),
+            expiry_watchdog: ExpiryWatchdogArgs::default(),
+            fees: FeeArgs::default(),
+            metrics_addr: None,
+            report_cycles: false,
+            state_file: "operator_state.json".into(),
+        }
+    }
+}
+
+impl OperatorConfig {
+    /// Reads the configuration from the given file, or the defaults if there is none, and
+    /// overrides it with the environment. The configuration still has to be validated.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let mut config = match path {
+            Some(path) => {
+                let contents = fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read config file {}", path.display()))?;
+                Self::from_toml(&contents)
+                    .with_context(|| format!("Failed to parse config file {}", path.display()))?
+            }
+            None => Self::default(),
+        };
+        config.apply_env(|name| env::var(name).ok())?;
+
+        Ok(config)
+    }
+
+    /// Parses the configuration from TOML, defaulting missing settings.
+    pub fn from_toml(s: &str) -> Result<Self> {
+        Ok(toml::from_str(s)?)
+    }
+
+    /// Checks that the settings the operator loop needs are set and consistent.
+    pub fn validate(&self) -> Result<()> {
+        if self.tendermint_rpc_urls.is_empty() {
+            bail!("no Tendermint RPC URL configured");
+        }
+        if !self.tendermint_witness_rpc_urls.is_empty()
+            && self.min_witness_matches > self.tendermint_witness_rpc_urls.len()
+        {
+            bail!(
+                "min_witness_matches is {}, but only {} witnesses are configured",
+                self.min_witness_matches,
+                self.tendermint_witness_rpc_urls.len()
+            );
+        }
+        if self.relay_targets.is_empty() {
+            bail!("no relay target configured");
+        }
+        if !self.proof_mode.is_onchain_verifiable() {
+            bail!(
+                "proof mode {:?} cannot be verified onchain, use plonk or groth16",
+                self.proof_mode
+            );
+        }
+        if self.trust_options.trusting_period == 0 {
+            bail!("trusting period must be greater than 0");
+        }
+        self.schedule.validate()?;
+        self.fees.validate()?;
+        Ok(())
+    }
+
+    /// Creates a client of the configured Tendermint RPC endpoints, cross-checked against the
+    /// configured witnesses.
+    pub fn tendermint_rpc_client(&self) -> Result<TendermintRPCClient> {
+        if self.tendermint_rpc_urls.is_empty() {
+            bail!("no Tendermint RPC URL configured");
+        }
+        Ok(TendermintRPCClient::new(self.tendermint_rpc_urls.clone())?
+            .with_witnesses(self.tendermint_witness_rpc_urls.clone())
+            .with_min_witness_matches(self.min_witness_matches))
+    }
+
+    /// Overrides the settings with the command line flags that are set.
+    pub fn apply_overrides(&mut self, overrides: ConfigOverrides) {
+        if let Some(relay_targets) = overrides.relay_targets {
+            self.relay_targets = relay_targets;
+        }
+        if let Some(proof_mode) = overrides.proof_mode {
+            self.proof_mode = proof_mode;
+        }
+        if let Some(prover) = overrides.prover {
+            self.prover = prover;
+        }
+
+        if let Some(trust_threshold) = overrides.trust_threshold {
+            self.trust_options.trust_threshold = trust_threshold;
+        }
+        if let Some(trusting_period) = overrides.trusting_period {
+            self.trust_options.trusting_period = trusting_period;
+        }
+        if let Some(clock_drift) = overrides.clock_drift {
+            self.trust_options.clock_drift = clock_drift;
+        }
+
+        if let Some(poll_interval) = overrides.poll_interval {
+            self.schedule.poll_interval = poll_interval;
+        }
+        if let Some(max_block_step) = overrides.max_block_step {
+            self.schedule.max_block_step = Some(max_block_step);
+        }
+        if let Some(min_block_step) = overrides.min_block_step {
+            self.schedule.min_block_step = min_block_step;
+        }
+        if let Some(target_policy) = overrides.target_policy {
+            self.schedule.target_policy = target_policy;
+        }
+
+        if let Some(thresholds) = overrides.expiry_warning_thresholds {
+            self.expiry_watchdog.expiry_warning_thresholds = thresholds;
+        }
+        if let Some(threshold) = overrides.urgent_expiry_threshold {
+            self.expiry_watchdog.urgent_expiry_threshold = threshold;
+        }
+
+        if let Some(max_fee_per_gas) = overrides.max_fee_per_gas {
+            self.fees.max_fee_per_gas = Some(max_fee_per_gas);
+        }
+        if let Some(max_priority_fee_per_gas) = overrides.max_priority_fee_per_gas {
+            self.fees.max_priority_fee_per_gas = Some(max_priority_fee_per_gas);
+        }
+        if let Some(replacement_blocks) = overrides.replacement_blocks {
+            self.fees.replacement_blocks = replacement_blocks;
+        }
+        if let Some(fee_bump_percent) = overrides.fee_bump_percent {
+            self.fees.fee_bump_percent = fee_bump_percent;
+        }
+        if let Some(max_replacements) = overrides.max_replacements {
+            self.fees.max_replacements = max_replacements;
+        }
+
+        if let Some(metrics_addr) = overrides.metrics_addr {
+            self.metrics_addr = Some(metrics_addr);
+        }
+        if overrides.report_cycles {
+            self.report_cycles = true;
+        }
+        if let Some(state_file) = overrides.state_file {
+            self.state_file = state_file;
+        }
+    }
+
+    /// Overrides the settings with the environment variables that are set, looking each one up
+    /// with `env`.
+    pub fn apply_env(&mut self, env: impl Fn(&str) -> Option<String>) -> Result<()> {
+        if let Some(urls) = env_var::<String>(&env, "TENDERMINT_RPC_URL")? {
+            self.tendermint_rpc_urls = split_urls(&urls);
+        }
+        if let Some(urls) = env_var::<String>(&env, "TENDERMINT_WITNESS_RPC_URLS")? {
+            self.tendermint_witness_rpc_urls = split_urls(&urls);
+        }
+        if let Some(min_witness_matches) = env_var(&env, "MIN_WITNESS_MATCHES")? {
+            self.min_witness_matches = min_witness_matches;
+        }
+        if let Some(relay_targets) = env_list(&env, "RELAY_TARGETS")? {
+            self.relay_targets = relay_targets;
+        } else if ["CHAIN_ID", "CONTRACT_ADDRESS"]
+            .iter()
+            .any(|name| env(name).is_some_and(|value| !value.is_empty()))
+        {
+            self.relay_targets = vec![RelayTarget::from_env(&env)?];
+        }
+        if let Some(proof_mode) = env_var::<String>(&env, "PROOF_MODE")? {
+            self.proof_mode = ProofMode::from_str(&proof_mode, true)
+                .map_err(|e| anyhow!("PROOF_MODE is invalid: {}", e))?;
+        }
+        if let Some(prover) = env_var::<String>(&env, "SP1_PROVER")? {
+            self.prover = ProverKind::from_str(&prover, true)
+                .map_err(|e| anyhow!("SP1_PROVER is invalid: {}", e))?;
+        }
+
+        if let Some(trust_threshold) = env_var::<String>(&env, "TRUST_THRESHOLD")? {
+            self.trust_options.trust_threshold =
+                parse_trust_threshold(&trust_threshold).context("TRUST_THRESHOLD is invalid")?;
+        }
+        if let Some(trusting_period) = env_var(&env, "TRUSTING_PERIOD")? {
+            self.trust_options.trusting_period = trusting_period;
+        }
+        if let Some(clock_drift) = env_var(&env, "CLOCK_DRIFT")? {
+            self.trust_options.clock_drift = clock_drift;
+        }
+
+        if let Some(poll_interval) = env_var(&env, "POLL_INTERVAL")? {
+            self.schedule.poll_interval = poll_interval;
+        }
+        if let Some(max_block_step) = env_var(&env, "MAX_BLOCK_STEP")? {
+            self.schedule.max_block_step = Some(max_block_step);
+        }
+        if let Some(min_block_step) = env_var(&env, "MIN_BLOCK_STEP")? {
+            self.schedule.min_block_step = min_block_step;
+        }
+        if let Some(target_policy) = env_var(&env, "TARGET_POLICY")? {
+            self.schedule.target_policy = target_policy;
+        }
+
+        if let Some(thresholds) = env_list(&env, "EXPIRY_WARNING_THRESHOLDS")? {
+            self.expiry_watchdog.expiry_warning_thresholds = thresholds;
+        }
+        if let Some(threshold) = env_var(&env, "URGENT_EXPIRY_THRESHOLD")? {
+            self.expiry_watchdog.urgent_expiry_threshold = threshold;
+        }
+
+        if let Some(max_fee_per_gas) = env_var(&env, "MAX_FEE_PER_GAS")? {
+            self.fees.max_fee_per_gas = Some(max_fee_per_gas);
+        }
+        if let Some(max_priority_fee_per_gas) = env_var(&env, "MAX_PRIORITY_FEE_PER_GAS")? {
+            self.fees.max_priority_fee_per_gas = Some(max_priority_fee_per_gas);
+        }
+        if let Some(replacement_blocks) = env_var(&env, "REPLACEMENT_BLOCKS")? {
+            self.fees.replacement_blocks = replacement_blocks;
+        }
+        if let Some(fee_bump_percent) = env_var(&env, "FEE_BUMP_PERCENT")? {
+            self.fees.fee_bump_percent = fee_bump_percent;
+        }
+        if let Some(max_replacements) = env_var(&env, "MAX_REPLACEMENTS")? {
+            self.fees.max_replacements = max_replacements;
+        }
+
+        if let Some(metrics_addr) = env_var(&env, "METRICS_ADDR")? {
+            self.metrics_addr = Some(metrics_addr);
+        }
+        if let Some(report_cycles) = env_var(&env, "REPORT_CYCLES")? {
+            self.report_cycles = report_cycles;
+        }
+        if let Some(state_file) = env_var(&env, "OPERATOR_STATE_FILE")? {
+            self.state_file = state_file;
+        }
+
+        Ok(())
+    }
+}
+
+/// Command line flags of the operator overriding the settings of the configuration file and the
+/// environment. Each flag is named after the setting it overrides.
+#[derive(Args, Debug, Clone, Default)]
+#[clap(about = None, long_about = None, next_help_heading = "Configuration overrides")]
+pub struct ConfigOverrides {
+    /// Comma-separated `SP1Tendermint` contracts to relay proofs to, each of the form
+    /// `CHAIN_ID:CONTRACT_ADDRESS:RPC_URL`.
+    #[clap(long, value_delimiter = ',')]
+    pub relay_targets: Option<Vec<RelayTarget>>,
+
+    /// The type of proof to relay. Must be verifiable onchain.
+    #[clap(long, value_enum)]
+    pub proof_mode: Option<ProofMode>,
+
+    /// The SP1 prover that generates the proofs.
+    #[clap(long, value_enum)]
+    pub prover: Option<ProverKind>,
+
+    /// Fraction of the trusted validator set's voting power that must sign the target block, e.g.
+    /// `2/3`.
+    #[clap(long, value_parser = parse_trust_threshold)]
+    pub trust_threshold: Option<TrustThreshold>,
+
+    /// How long a validator set is trusted for, in seconds.
+    #[clap(long)]
+    pub trusting_period: Option<u64>,
+
+    /// Maximum amount the local clock may drift behind a block timestamp, in seconds.
+    #[clap(long)]
+    pub clock_drift: Option<u64>,
+
+    /// Seconds to wait between iterations of the operator loop.
+    #[clap(long)]
+    pub poll_interval: Option<u64>,
+
+    /// Maximum number of blocks a single update may advance the contract by.
+    #[clap(long)]
+    pub max_block_step: Option<u64>,
+
+    /// Minimum number of blocks an update must advance the contract by to be worth proving.
+    #[clap(long)]
+    pub min_block_step: Option<u64>,
+
+    /// How to pick the target block from the latest block: `latest`, `confirmations:N` or
+    /// `aligned:N`.
+    #[clap(long)]
+    pub target_policy: Option<TargetPolicy>,
+
+    /// Comma-separated times left before the trusting period of the contract's latest block
+    /// expires, in seconds, at which to log a warning.
+    #[clap(long, value_delimiter = ',')]
+    pub expiry_warning_thresholds: Option<Vec<u64>>,
+
+    /// Time left before the trusting period expires, in seconds, below which an update is proven
+    /// immediately.
+    #[clap(long)]
+    pub urgent_expiry_threshold: Option<u64>,
+
+    /// Maximum fee per gas, in wei, of the transactions the operator sends.
+    #[clap(long)]
+    pub max_fee_per_gas: Option<u128>,
+
+    /// Maximum priority fee per gas, in wei, of the transactions the operator sends.
+    #[clap(long)]
+    pub max_priority_fee_per_gas: Option<u128>,
+
+    /// Number of blocks after which a transaction that is not mined is replaced.
+    #[clap(long)]
+    pub replacement_blocks: Option<u64>,
+
+    /// Percentage by which the fees of a replacement transaction are bumped.
+    #[clap(long)]
+    pub fee_bump_percent: Option<u64>,
+
+    /// Number of times a transaction is replaced before waiting for it times out.
+    #[clap(long)]
+    pub max_replacements: Option<u32>,
+
+    /// Address to serve Prometheus metrics on, at `/metrics`.
+    #[clap(long)]
+    pub metrics_addr: Option<SocketAddr>,
+
+    /// Execute each update before proving it, to report its cycle count.
+    #[clap(long)]
+    pub report_cycles: bool,
+
+    /// File the operator persists its in-flight jobs, unsubmitted proofs and pending transactions
+    /// to.
+    #[clap(long)]
+    pub state_file: Option<PathBuf>,
+}
+
+/// Parses the environment variable, or returns `None` if it is unset or empty, e.g. because it is
+/// left blank in a `.env` file.
+pub(crate) fn env_var<T: FromStr>(
+    env: &impl Fn(&str) -> Option<String>,
+    name: &str,
+) -> Result<Option<T>>
+where
+    T::Err: Display,
+{
+    match env(name) {
+        Some(value) if !value.trim().is_empty() => value
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|e| anyhow!("{} is invalid: {}", name, e)),
+        _ => Ok(None),
+    }
+}
+
+/// Parses the comma-separated list in the environment variable, like `env_var`.
+fn env_list<T: FromStr>(env: &impl Fn(&str) -> Option<String>, name: &str) -> Result<Option<Vec<T>>>
+where
+    T::Err: Display,
+{
+    let Some(value) = env_var::<String>(env, name)? else {
+        return Ok(None);
+    };
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            item.parse()
+                .map_err(|e| anyhow!("{} is invalid: {}", name, e))
+        })
+        .collect::<Result<_>>()
+        .map(Some)
+}
@@ -1,78 +1,392 @@
-use anyhow::Result;
-use ethers::{
-    middleware::SignerMiddleware,
-    providers::{Http, Middleware, Provider},
-    signers::{LocalWallet, Signer},
-    types::{
-        transaction::eip2718::TypedTransaction, Address, TransactionReceipt, TransactionRequest,
+use crate::{
+    bindings::{SP1Tendermint, SP1VerifierGateway},
+    fees::{FeeArgs, Fees},
+};
+use alloy::{
+    consensus::Transaction,
+    network::{Ethereum, EthereumWallet, NetworkWallet, TransactionBuilder},
+    providers::{
+        fillers::{FillProvider, JoinFill, RecommendedFillers, WalletFiller},
+        Identity, Provider, ProviderBuilder, RootProvider,
+    },
+    rpc::types::{TransactionReceipt, TransactionRequest},
+    transports::{
+        http::{Client, Http},
+        TransportError,
     },
 };
-use std::env;
+use alloy_primitives::{Address, Bytes, FixedBytes, TxHash};
+use alloy_sol_types::{SolCall, SolInterface};
+use anyhow::Result;
+use log::warn;
+use std::{fmt, time::Duration};
+use thiserror::Error;
+
+/// How often to check whether a sent transaction was mined.
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(4);
+
+/// A custom error a call to the `SP1Tendermint` contract reverted with, including the errors of the
+/// SP1 verifier gateway and verifiers it verifies proofs with.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RevertReason {
+    /// The trusted block of the update is not the contract's latest block, e.g. because the
+    /// contract was updated since the proof was generated.
+    #[error("InvalidTrustedHeader(): the trusted header is not the contract's latest header")]
+    InvalidTrustedHeader,
+    /// The proof verified headers with different light client options than the contract's.
+    #[error("InvalidTrustOptions(): the proof's trust options are not the contract's")]
+    InvalidTrustOptions,
+    /// The proof is of a different Tendermint chain than the contract's.
+    #[error("InvalidChainId(): the proof is of another chain")]
+    InvalidChainId,
+    /// The public values of a misbehaviour proof don't prove misbehaviour.
+    #[error("InvalidMisbehaviour(): the public values do not prove misbehaviour")]
+    InvalidMisbehaviour,
+    /// The light client was frozen by a proof of misbehaviour.
+    #[error("ClientFrozen(): the light client is frozen")]
+    ClientFrozen,
+    /// The gateway has no verifier for the proof's verifier selector, e.g. because the proof was
+    /// generated with a different SP1 version.
+    #[error("RouteNotFound({0}): no verifier for the proof's selector")]
+    RouteNotFound(FixedBytes<4>),
+    /// The gateway's verifier for the proof's verifier selector was frozen.
+    #[error("RouteIsFrozen({0}): the verifier for the proof's selector is frozen")]
+    RouteIsFrozen(FixedBytes<4>),
+    /// The proof was sent to a verifier of a different SP1 version or proof system.
+    #[error(
+        "WrongVerifierSelector({received}, {expected}): the proof is for a different verifier"
+    )]
+    WrongVerifierSelector {
+        received: FixedBytes<4>,
+        expected: FixedBytes<4>,
+    },
+    /// The proof does not verify against the public values and the program's verification key.
+    #[error("InvalidProof(): the proof is invalid")]
+    InvalidProof,
+    /// The revert data is not a known error.
+    #[error("unknown revert data {0}")]
+    Unknown(Bytes),
+}
+
+impl RevertReason {
+    /// Decodes revert data against the errors of the `SP1Tendermint` contract and the SP1 verifier
+    /// gateway.
+    pub fn decode(data: &[u8]) -> Self {
+        use SP1Tendermint::SP1TendermintErrors as Contract;
+        use SP1VerifierGateway::SP1VerifierGatewayErrors as Gateway;
+
+        if let Ok(error) = Contract::abi_decode(data, true) {
+            return match error {
+                Contract::InvalidTrustedHeader(_) => RevertReason::InvalidTrustedHeader,
+                Contract::InvalidTrustOptions(_) => RevertReason::InvalidTrustOptions,
+                Contract::InvalidChainId(_) => RevertReason::InvalidChainId,
+                Contract::InvalidMisbehaviour(_) => RevertReason::InvalidMisbehaviour,
+                Contract::ClientFrozen(_) => RevertReason::ClientFrozen,
+            };
+        }
+        match Gateway::abi_decode(data, true) {
+            Ok(Gateway::RouteNotFound(e)) => RevertReason::RouteNotFound(e.selector),
+            Ok(Gateway::RouteIsFrozen(e)) => RevertReason::RouteIsFrozen(e.selector),
+            Ok(Gateway::WrongVerifierSelector(e)) => RevertReason::WrongVerifierSelector {
+                received: e.received,
+                expected: e.expected,
+            },
+            Ok(Gateway::InvalidProof(_)) => RevertReason::InvalidProof,
+            Err(_) => RevertReason::Unknown(data.to_vec().into()),
+        }
+    }
+}
+
+/// Errors returned by the `ContractClient`.
+#[derive(Debug, Error)]
+pub enum ContractError {
+    /// The call reverted with revert data, decoded against the contract's errors.
+    #[error("call reverted with {0}")]
+    Revert(RevertReason),
+    /// The transaction, and every transaction replacing it, was not mined within the given number
+    /// of blocks.
+    #[error("transaction {tx_hash} was not mined within {blocks} blocks")]
+    Timeout { tx_hash: TxHash, blocks: u64 },
+    /// The request to the Ethereum node failed, or the call failed without revert data.
+    #[error(transparent)]
+    Provider(#[from] anyhow::Error),
+}
+
+impl ContractError {
+    /// Converts a transport error, decoding its revert data if the call reverted.
+    fn from_transport(e: TransportError) -> Self {
+        match e.as_error_resp().and_then(|e| e.as_revert_data()) {
+            Some(data) => ContractError::Revert(RevertReason::decode(&data)),
+            None => ContractError::Provider(e.into()),
+        }
+    }
+}
 
-/// Wrapper of a `SignerMiddleware` client to send transactions to the given
-/// contract's `Address`.
+/// The outcome of simulating a transaction against the contract.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Simulation {
+    /// The transaction would succeed and use about the estimated gas.
+    Success { gas_estimate: u64 },
+    /// The transaction would revert with the given error.
+    Revert(RevertReason),
+}
+
+/// An HTTP provider that fills in the gas, nonce and chain ID of transactions and signs them with
+/// the operator's wallet.
+type SignerProvider = FillProvider<
+    JoinFill<
+        JoinFill<Identity, <Ethereum as RecommendedFillers>::RecommendedFillers>,
+        WalletFiller<EthereumWallet>,
+    >,
+    RootProvider<Http<Client>>,
+    Http<Client>,
+    Ethereum,
+>;
+
+/// Wrapper of an alloy provider with a signer to send transactions to the given contract's
+/// `Address`.
 pub struct ContractClient {
     chain_id: u64,
-    client: SignerMiddleware<Provider<Http>, LocalWallet>,
+    from: Address,
+    provider: SignerProvider,
+    fees: FeeArgs,
     pub contract: Address,
 }
 
-impl Default for ContractClient {
-    fn default() -> Self {
-        let chain_id = env::var("CHAIN_ID")
-            .expect("CHAIN_ID not set")
-            .parse::<u64>()
-            .expect("CHAIN_ID not a valid u64");
-        let rpc_url = env::var("RPC_URL").expect("RPC_URL not set");
-        let mut private_key = env::var("PRIVATE_KEY").expect("PRIVATE_KEY not set");
-        // Strip the `0x` prefix from the private key (if present).
-        if let Some(stripped) = private_key.strip_prefix("0x") {
-            private_key = stripped.to_string();
-        }
-        let contract = env::var("CONTRACT_ADDRESS").expect("CONTRACT_ADDRESS not set");
-
-        Self::new(chain_id, &rpc_url, &private_key, &contract)
-            .expect("Failed to create ContractClient")
+impl fmt::Display for ContractClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "contract {} on chain {}", self.contract, self.chain_id)
     }
 }
 
 impl ContractClient {
-    /// Creates a new `ContractClient`.
-    pub fn new(chain_id: u64, rpc_url: &str, private_key: &str, contract: &str) -> Result<Self> {
-        let provider = Provider::<Http>::try_from(rpc_url)?;
-
-        let wallet: LocalWallet = private_key.parse::<LocalWallet>()?.with_chain_id(chain_id);
-        let client = SignerMiddleware::new(provider.clone(), wallet.clone());
-        let contract = contract.parse::<Address>()?;
+    /// Creates a new `ContractClient` sending transactions signed by the given wallet.
+    pub fn new(
+        chain_id: u64,
+        rpc_url: &str,
+        wallet: EthereumWallet,
+        contract: Address,
+    ) -> Result<Self> {
+        let from = NetworkWallet::<Ethereum>::default_signer_address(&wallet);
+        let provider = ProviderBuilder::new()
+            .with_recommended_fillers()
+            .wallet(wallet)
+            .on_http(rpc_url.parse()?);
 
         Ok(ContractClient {
             chain_id,
-            client,
+            from,
+            provider,
+            fees: FeeArgs::default(),
             contract,
         })
     }
 
+    /// The ID of the chain the contract is deployed on.
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    /// Sets the fee caps of the transactions the client sends, and when it replaces them.
+    pub fn with_fee_args(mut self, fees: FeeArgs) -> Self {
+        self.fees = fees;
+        self
+    }
+
     /// Read data from the contract using calldata.
-    pub async fn read(&self, calldata: Vec<u8>) -> Result<Vec<u8>> {
-        let mut tx = TypedTransaction::default();
-        tx.set_chain_id(self.chain_id);
-        tx.set_to(self.contract);
-        tx.set_data(calldata.into());
-        let data = self.client.call(&tx, None).await?;
+    pub async fn read(&self, calldata: Vec<u8>) -> Result<Vec<u8>, ContractError> {
+        let tx = TransactionRequest::default()
+            .with_chain_id(self.chain_id)
+            .with_to(self.contract)
+            .with_input(calldata);
+        let data = self
+            .provider
+            .call(&tx)
+            .await
+            .map_err(ContractError::from_transport)?;
 
         Ok(data.to_vec())
     }
 
-    /// Send a transaction with the given calldata.
-    pub async fn send(&self, calldata: Vec<u8>) -> Result<Option<TransactionReceipt>> {
-        let tx = TransactionRequest::new()
-            .chain_id(self.chain_id)
-            .to(self.contract)
-            .from(self.client.address())
-            .data(calldata);
+    /// Call a view function of the contract and decode its return values.
+    pub async fn call<C: SolCall>(&self, call: C) -> Result<C::Return, ContractError> {
+        let data = self.read(call.abi_encode()).await?;
+        C::abi_decode_returns(&data, true).map_err(|e| ContractError::Provider(e.into()))
+    }
+
+    /// Simulate a transaction with the given calldata from the signer with `eth_call` and
+    /// `eth_estimateGas`, without sending it.
+    pub async fn simulate(&self, calldata: Vec<u8>) -> Result<Simulation, ContractError> {
+        let tx = self.transaction_request(calldata);
+
+        if let Err(e) = self.provider.call(&tx).await {
+            return match ContractError::from_transport(e) {
+                ContractError::Revert(reason) => Ok(Simulation::Revert(reason)),
+                e => Err(e),
+            };
+        }
+        let gas_estimate = self
+            .provider
+            .estimate_gas(&tx)
+            .await
+            .map_err(ContractError::from_transport)?;
+
+        Ok(Simulation::Success { gas_estimate })
+    }
+
+    /// Send a transaction with the given calldata, and wait for it or a transaction replacing it to
+    /// be mined.
+    pub async fn send(
+        &self,
+        calldata: Vec<u8>,
+    ) -> Result<Option<TransactionReceipt>, ContractError> {
+        let tx_hash = self.submit(calldata).await?;
+        self.wait_for_receipt(tx_hash, |_, _| Ok(())).await
+    }
+
+    /// Send a transaction with the given calldata without waiting for it to be mined, and return
+    /// its hash. The fees are estimated and capped at the client's fee caps. If the transaction
+    /// would revert, the gas estimation fails with the decoded revert reason and the transaction is
+    /// not sent.
+    pub async fn submit(&self, calldata: Vec<u8>) -> Result<TxHash, ContractError> {
+        let fees = self.estimate_fees().await?;
+        let tx = self
+            .transaction_request(calldata)
+            .with_max_fee_per_gas(fees.max_fee_per_gas)
+            .with_max_priority_fee_per_gas(fees.max_priority_fee_per_gas);
+        let pending_tx = self
+            .provider
+            .send_transaction(tx)
+            .await
+            .map_err(ContractError::from_transport)?;
 
-        let tx = self.client.send_transaction(tx, None).await?.await?;
+        Ok(*pending_tx.tx_hash())
+    }
+
+    /// Wait for the transaction with the given hash to be mined. If it is not mined within the
+    /// replacement blocks, it is replaced with a transaction with the same nonce and bumped fees,
+    /// and `on_replaced` is called with the hashes of the replaced and the replacing transaction.
+    /// Returns the receipt of whichever of them is mined, `None` if the transaction was dropped
+    /// from the mempool, and a timeout error once the last replacement is not mined either.
+    pub async fn wait_for_receipt(
+        &self,
+        tx_hash: TxHash,
+        mut on_replaced: impl FnMut(TxHash, TxHash) -> Result<()>,
+    ) -> Result<Option<TransactionReceipt>, ContractError> {
+        // A transaction the node doesn't know about was dropped, and would never be mined.
+        let Some(tx) = self
+            .provider
+            .get_transaction_by_hash(tx_hash)
+            .await
+            .map_err(ContractError::from_transport)?
+        else {
+            return Ok(None);
+        };
+
+        let mut fees = Fees {
+            max_fee_per_gas: tx.max_fee_per_gas(),
+            max_priority_fee_per_gas: tx
+                .max_priority_fee_per_gas()
+                .unwrap_or(tx.max_fee_per_gas()),
+        };
+        let mut tx_hashes = vec![tx_hash];
+        let mut replacements = 0;
+        let mut replace_at = self.block_number().await? + self.fees.replacement_blocks;
+        loop {
+            // Any transaction with the nonce may be mined, not only the latest replacement.
+            for tx_hash in tx_hashes.iter().rev() {
+                let receipt = self
+                    .provider
+                    .get_transaction_receipt(*tx_hash)
+                    .await
+                    .map_err(ContractError::from_transport)?;
+                if receipt.is_some() {
+                    return Ok(receipt);
+                }
+            }
+
+            let block_number = self.block_number().await?;
+            if block_number >= replace_at {
+                let latest_tx_hash = *tx_hashes.last().unwrap();
+                if replacements == self.fees.max_replacements {
+                    return Err(ContractError::Timeout {
+                        tx_hash: latest_tx_hash,
+                        blocks: (u64::from(self.fees.max_replacements) + 1)
+                            * self.fees.replacement_blocks,
+                    });
+                }
+                replacements += 1;
+                replace_at = block_number + self.fees.replacement_blocks;
+
+                let Some(bumped) = self.fees.bump(fees) else {
+                    warn!(
+                        "Transaction {} is not mined, but its fees are at the fee caps and cannot be bumped",
+                        latest_tx_hash
+                    );
+                    continue;
+                };
+                let replacement = TransactionRequest::default()
+                    .with_chain_id(self.chain_id)
+                    .with_from(self.from)
+                    .with_kind(tx.kind())
+                    .with_value(tx.value())
+                    .with_input(tx.input().clone())
+                    .with_nonce(tx.nonce())
+                    .with_gas_limit(tx.gas_limit())
+                    .with_max_fee_per_gas(bumped.max_fee_per_gas)
+                    .with_max_priority_fee_per_gas(bumped.max_priority_fee_per_gas);
+                // The replaced transaction may have been mined in the meantime, in which case the
+                // replacement is rejected and its receipt is found on the next check.
+                match self.provider.send_transaction(replacement).await {
+                    Ok(pending_tx) => {
+                        let replacement_tx_hash = *pending_tx.tx_hash();
+                        warn!(
+                            "Transaction {} was not mined within {} blocks, replaced it with transaction {} with max fee per gas {} and max priority fee per gas {}",
+                            latest_tx_hash,
+                            self.fees.replacement_blocks,
+                            replacement_tx_hash,
+                            bumped.max_fee_per_gas,
+                            bumped.max_priority_fee_per_gas
+                        );
+                        on_replaced(latest_tx_hash, replacement_tx_hash)?;
+                        tx_hashes.push(replacement_tx_hash);
+                        fees = bumped;
+                    }
+                    Err(e) => warn!("Failed to replace transaction {}: {}", latest_tx_hash, e),
+                }
+            }
+
+            tokio::time::sleep(RECEIPT_POLL_INTERVAL).await;
+        }
+    }
+
+    /// The estimated EIP-1559 fees of a transaction, capped at the client's fee caps.
+    async fn estimate_fees(&self) -> Result<Fees, ContractError> {
+        let estimate = self
+            .provider
+            .estimate_eip1559_fees(None)
+            .await
+            .map_err(ContractError::from_transport)?;
+
+        Ok(self.fees.cap(Fees {
+            max_fee_per_gas: estimate.max_fee_per_gas,
+            max_priority_fee_per_gas: estimate.max_priority_fee_per_gas,
+        }))
+    }
+
+    async fn block_number(&self) -> Result<u64, ContractError> {
+        self.provider
+            .get_block_number()
+            .await
+            .map_err(ContractError::from_transport)
+    }
 
-        Ok(tx)
+    /// A transaction from the signer to the contract with the given calldata.
+    fn transaction_request(&self, calldata: Vec<u8>) -> TransactionRequest {
+        TransactionRequest::default()
+            .with_chain_id(self.chain_id)
+            .with_from(self.from)
+            .with_to(self.contract)
+            .with_input(calldata)
     }
 }
@@ -0,0 +1,117 @@
+use crate::{
+    util::{Divergence, TendermintRPCClient},
+    verify::{verify_natively, verify_natively_at, verify_time},
+};
+use anyhow::{anyhow, bail, Result};
+use tendermint_light_client_verifier::{options::Options, types::LightBlock, Verdict};
+
+/// Evidence of a light client attack: two conflicting headers at the same height that are both
+/// sufficiently signed by the validators of a common block, which descends from a trusted block.
+#[derive(Debug, Clone)]
+pub struct LightClientAttackEvidence {
+    /// The verified path from the trusted block to the common block both conflicting blocks verify
+    /// against. Only contains the trusted block if it is the common block.
+    pub common_path: Vec<LightBlock>,
+    /// The conflicting block served by the primary RPC endpoint.
+    pub primary_light_block: LightBlock,
+    /// The conflicting block served by the witness RPC endpoint.
+    pub witness_light_block: LightBlock,
+}
+
+impl LightClientAttackEvidence {
+    /// Assembles the evidence for a divergence between the primary and a witness, using the last
+    /// block of the given path from the trusted block as the common block. The conflicting blocks
+    /// are only evidence of an attack if `verify` succeeds, otherwise one of the endpoints is
+    /// simply faulty.
+    pub async fn collect(
+        tendermint_rpc_client: &TendermintRPCClient,
+        common_path: Vec<LightBlock>,
+        divergence: &Divergence,
+    ) -> Result<Self> {
+        if common_path.is_empty() {
+            bail!("The path to the common block must contain at least the trusted block");
+        }
+        let witness = tendermint_rpc_client
+            .witnesses()
+            .iter()
+            .find(|witness| witness.current_url() == divergence.witness_url)
+            .ok_or_else(|| anyhow!("Unknown witness {}", divergence.witness_url))?;
+
+        let primary_peer_id = tendermint_rpc_client.get_peer_id().await?;
+        let primary_light_block = tendermint_rpc_client
+            .get_light_block(divergence.height, primary_peer_id)
+            .await?;
+        let witness_peer_id = witness.get_peer_id().await?;
+        let witness_light_block = witness
+            .get_light_block(divergence.height, witness_peer_id)
+            .await?;
+
+        Ok(Self {
+            common_path,
+            primary_light_block,
+            witness_light_block,
+        })
+    }
+
+    /// The block both conflicting blocks verify against.
+    pub fn common_light_block(&self) -> &LightBlock {
+        self.common_path
+            .last()
+            .expect("the path to the common block is not empty")
+    }
+
+    /// The height of the conflicting blocks.
+    pub fn conflict_height(&self) -> u64 {
+        self.primary_light_block.signed_header.header.height.value()
+    }
+
+    /// Checks natively that the conflicting blocks are different headers at the same height that
+    /// both verify against the common block, and that the common block verifies along the path
+    /// from the trusted block, as the zkVM program does.
+    pub fn verify(&self, options: &Options) -> Result<()> {
+        let primary_header = &self.primary_light_block.signed_header.header;
+        let witness_header = &self.witness_light_block.signed_header.header;
+        if primary_header.height != witness_header.height {
+            bail!(
+                "Conflicting blocks are at different heights {} and {}",
+                primary_header.height,
+                witness_header.height
+            );
+        }
+        if primary_header.hash() == witness_header.hash() {
+            bail!("Blocks at height {} do not conflict", primary_header.height);
+        }
+
+        // Every hop of the path is verified at the same time as in the zkVM program.
+        let path_verify_time = verify_time(&self.primary_light_block)?;
+        for hop in self.common_path.windows(2) {
+            match verify_natively_at(&hop[0], &hop[1], options, path_verify_time)? {
+                Verdict::Success => {}
+                v => bail!(
+                    "Block {} on the path to the common block does not verify against {}: {:?}",
+                    hop[1].signed_header.header.height,
+                    hop[0].signed_header.header.height,
+                    v
+                ),
+            }
+        }
+
+        for (source, light_block) in [
+            ("primary", &self.primary_light_block),
+            ("witness", &self.witness_light_block),
+        ] {
+            match verify_natively(self.common_light_block(), light_block, options)? {
+                Verdict::Success => {}
+                v => bail!(
+                    "The {} block at height {} does not verify against the common block {}: {:?}",
+                    source,
+                    primary_header.height,
+                    self.common_light_block().signed_header.header.height,
+                    v
+                ),
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Deserializer};
+
+/// The percentage by which nodes require both fees of a replacement transaction to exceed the fees
+/// of the transaction it replaces.
+pub const MIN_FEE_BUMP_PERCENT: u64 = 10;
+
+const DEFAULT_FEE_BUMP_PERCENT: u64 = 12;
+const DEFAULT_REPLACEMENT_BLOCKS: u64 = 3;
+const DEFAULT_MAX_REPLACEMENTS: u32 = 5;
+
+/// The EIP-1559 fees per gas of a transaction, in wei.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fees {
+    pub max_fee_per_gas: u128,
+    pub max_priority_fee_per_gas: u128,
+}
+
+/// The EIP-1559 fees the operator pays for its transactions, and when it replaces a transaction
+/// that is not mined with one with the same nonce and bumped fees.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeeArgs {
+    /// Maximum fee per gas, in wei, of the transactions the operator sends. The estimated fee is
+    /// used uncapped if unset.
+    #[serde(deserialize_with = "deserialize_fee")]
+    pub max_fee_per_gas: Option<u128>,
+
+    /// Maximum priority fee per gas, in wei, of the transactions the operator sends. The estimated
+    /// priority fee is used uncapped if unset.
+    #[serde(deserialize_with = "deserialize_fee")]
+    pub max_priority_fee_per_gas: Option<u128>,
+
+    /// Number of blocks after which a transaction that is not mined is replaced.
+    pub replacement_blocks: u64,
+
+    /// Percentage by which the fees of a replacement transaction are bumped. Nodes reject
+    /// replacements bumped by less than 10%.
+    pub fee_bump_percent: u64,
+
+    /// Number of times a transaction is replaced before waiting for it times out.
+    pub max_replacements: u32,
+}
+
+impl Default for FeeArgs {
+    fn default() -> Self {
+        Self {
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            replacement_blocks: DEFAULT_REPLACEMENT_BLOCKS,
+            fee_bump_percent: DEFAULT_FEE_BUMP_PERCENT,
+            max_replacements: DEFAULT_MAX_REPLACEMENTS,
+        }
+    }
+}
+
+impl FeeArgs {
+    /// Checks that the caps are consistent and that replacements would be accepted by nodes.
+    pub fn validate(&self) -> Result<()> {
+        if let (Some(max_fee), Some(max_priority_fee)) =
+            (self.max_fee_per_gas, self.max_priority_fee_per_gas)
+        {
+            if max_priority_fee > max_fee {
+                bail!(
+                    "max priority fee per gas {} is greater than max fee per gas {}",
+                    max_priority_fee,
+                    max_fee
+                );
+            }
+        }
+        if self.replacement_blocks == 0 {
+            bail!("replacement blocks must be greater than 0");
+        }
+        if self.fee_bump_percent < MIN_FEE_BUMP_PERCENT {
+            bail!(
+                "fee bump percent {} is less than {}, so replacements would be rejected",
+                self.fee_bump_percent,
+                MIN_FEE_BUMP_PERCENT
+            );
+        }
+        Ok(())
+    }
+
+    /// Caps the fees at the configured maximums. The priority fee never exceeds the max fee.
+    pub fn cap(&self, fees: Fees) -> Fees {
+        let max_fee_per_gas = self
+            .max_fee_per_gas
+            .map_or(fees.max_fee_per_gas, |cap| fees.max_fee_per_gas.min(cap));
+        let max_priority_fee_per_gas = self
+            .max_priority_fee_per_gas
+            .map_or(fees.max_priority_fee_per_gas, |cap| {
+                fees.max_priority_fee_per_gas.min(cap)
+            })
+            .min(max_fee_per_gas);
+
+        Fees {
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+        }
+    }
+
+    /// The capped fees of a transaction replacing one with the given fees. Returns `None` if the
+    /// caps don't leave room for a bump nodes would accept.
+    pub fn bump(&self, fees: Fees) -> Option<Fees> {
+        let bumped = self.cap(Fees {
+            max_fee_per_gas: bump(fees.max_fee_per_gas, self.fee_bump_percent),
+            max_priority_fee_per_gas: bump(fees.max_priority_fee_per_gas, self.fee_bump_percent),
+        });
+
+        // Nodes compare against the old fees bumped by the minimum, rounded down.
+        let accepted = |new: u128, old: u128| {
+            new >= old.saturating_mul(100 + MIN_FEE_BUMP_PERCENT as u128) / 100
+        };
+        (accepted(bumped.max_fee_per_gas, fees.max_fee_per_gas)
+            && accepted(
+                bumped.max_priority_fee_per_gas,
+                fees.max_priority_fee_per_gas,
+            ))
+        .then_some(bumped)
+    }
+}
+
+/// Increases the fee by the given percentage, rounded up.
+fn bump(fee: u128, percent: u64) -> u128 {
+    fee.saturating_mul(100 + percent as u128).div_ceil(100)
+}
+
+/// Deserializes an optional fee per gas in wei. TOML integers are 64-bit, which is enough for any
+/// fee per gas.
+fn deserialize_fee<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u128>, D::Error> {
+    Ok(Option::<u64>::deserialize(deserializer)?.map(u128::from))
+}
@@ -1,65 +1,334 @@
+use alloy_primitives::B256;
+use anyhow::Result;
+use bindings::TendermintOutput;
+use clap::ValueEnum;
+use evidence::LightClientAttackEvidence;
+use serde::{Deserialize, Serialize};
 use sp1_sdk::{
-    EnvProver, ProverClient, SP1ProofWithPublicValues, SP1ProvingKey, SP1Stdin, SP1VerifyingKey,
+    network::{prover::NetworkProver, Error as NetworkError},
+    CpuProver, CudaProver, Prover, ProverClient, SP1ProofMode, SP1ProofWithPublicValues,
+    SP1ProvingKey, SP1Stdin, SP1VerifyingKey,
 };
-use tendermint_light_client_verifier::types::LightBlock;
+use std::collections::BTreeMap;
+use tendermint_light_client_verifier::{options::Options, types::LightBlock};
 
+pub mod bindings;
+pub mod bisection;
+pub mod config;
 pub mod contract;
+pub mod evidence;
+pub mod fees;
+pub mod metrics;
+pub mod options;
+pub mod relay;
+pub mod schedule;
+pub mod signer;
+pub mod state;
 mod types;
 pub mod util;
+pub mod verify;
+pub mod watchdog;
 
 // The path to the ELF file for the Succinct zkVM program.
 pub const TENDERMINT_ELF: &[u8] = include_bytes!("../../program/elf/tendermint-light-client");
 
+/// The mode of the Tendermint program that proves an update.
+const MODE_UPDATE: u8 = 0;
+/// The mode of the Tendermint program that proves misbehaviour.
+const MODE_MISBEHAVIOUR: u8 = 1;
+
+/// The result of executing the Tendermint program without generating a proof.
+#[derive(Debug)]
+pub struct TendermintExecution {
+    /// The total number of cycles executed.
+    pub cycles: u64,
+    /// The number of invocations of each syscall and precompile that was used.
+    pub syscall_counts: BTreeMap<String, u64>,
+    /// The decoded public values.
+    pub output: TendermintOutput,
+}
+
+/// The type of proof generated by the `TendermintProver`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProofMode {
+    /// A core proof, whose size scales with the number of cycles.
+    Core,
+    /// A compressed proof of constant size.
+    Compressed,
+    /// A PLONK proof, verifiable onchain by the SP1 PLONK verifier.
+    #[default]
+    Plonk,
+    /// A Groth16 proof, verifiable onchain by the SP1 Groth16 verifier.
+    Groth16,
+}
+
+impl ProofMode {
+    /// Whether proofs of this mode can be verified by an onchain SP1 verifier.
+    pub fn is_onchain_verifiable(&self) -> bool {
+        matches!(self, ProofMode::Plonk | ProofMode::Groth16)
+    }
+}
+
+impl From<ProofMode> for SP1ProofMode {
+    fn from(mode: ProofMode) -> Self {
+        match mode {
+            ProofMode::Core => SP1ProofMode::Core,
+            ProofMode::Compressed => SP1ProofMode::Compressed,
+            ProofMode::Plonk => SP1ProofMode::Plonk,
+            ProofMode::Groth16 => SP1ProofMode::Groth16,
+        }
+    }
+}
+
+/// The SP1 prover that generates the proofs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProverKind {
+    /// Mock proofs, which are only accepted by a mock verifier.
+    Mock,
+    /// Local proofs on the CPU.
+    #[default]
+    #[value(alias = "local")]
+    #[serde(alias = "local")]
+    Cpu,
+    /// Local proofs on a GPU.
+    Cuda,
+    /// Proofs from the Succinct prover network, authenticated with `NETWORK_PRIVATE_KEY` and sent
+    /// to `NETWORK_RPC_URL`.
+    Network,
+}
+
+/// The SP1 prover of a `ProverKind`.
+enum ProverClientKind {
+    Cpu(CpuProver),
+    Cuda(CudaProver),
+    Network(NetworkProver),
+}
+
 pub struct TendermintProver {
-    pub prover_client: EnvProver,
+    prover_client: ProverClientKind,
     pub pkey: SP1ProvingKey,
     pub vkey: SP1VerifyingKey,
+    pub proof_mode: ProofMode,
 }
 
 impl Default for TendermintProver {
     fn default() -> Self {
-        Self::new()
+        Self::new(ProofMode::default(), ProverKind::default())
     }
 }
 
 impl TendermintProver {
-    pub fn new() -> Self {
+    pub fn new(proof_mode: ProofMode, prover: ProverKind) -> Self {
         log::info!("Initializing SP1 ProverClient...");
-        let prover_client = ProverClient::from_env();
-        let (pkey, vkey) = prover_client.setup(TENDERMINT_ELF);
+        let prover_client = match prover {
+            ProverKind::Mock => ProverClientKind::Cpu(ProverClient::builder().mock().build()),
+            ProverKind::Cpu => ProverClientKind::Cpu(ProverClient::builder().cpu().build()),
+            ProverKind::Cuda => ProverClientKind::Cuda(ProverClient::builder().cuda().build()),
+            ProverKind::Network => {
+                ProverClientKind::Network(ProverClient::builder().network().build())
+            }
+        };
+        let (pkey, vkey) = match &prover_client {
+            ProverClientKind::Cpu(prover) => prover.setup(TENDERMINT_ELF),
+            ProverClientKind::Cuda(prover) => prover.setup(TENDERMINT_ELF),
+            ProverClientKind::Network(prover) => prover.setup(TENDERMINT_ELF),
+        };
         log::info!("SP1 ProverClient initialized");
         Self {
             prover_client,
             pkey,
             vkey,
+            proof_mode,
+        }
+    }
+
+    /// The prover network client, if proofs are generated by the Succinct prover network.
+    fn network_prover(&self) -> Option<&NetworkProver> {
+        match &self.prover_client {
+            ProverClientKind::Network(prover) => Some(prover),
+            _ => None,
         }
     }
 
-    /// Generate a proof of an update from trusted_light_block to target_light_block. Returns an
-    /// SP1Groth16Proof.
+    /// Whether proofs are generated by the Succinct prover network, and can be requested with
+    /// `request_tendermint_proof_for_path`.
+    pub fn is_network(&self) -> bool {
+        self.network_prover().is_some()
+    }
+
+    /// Request a proof of an update along a path of light blocks from the prover network, like
+    /// `generate_tendermint_proof_for_path`, without waiting for it. Returns the ID of the request
+    /// to wait for the proof with.
+    pub async fn request_tendermint_proof_for_path(
+        &self,
+        light_blocks: &[LightBlock],
+        options: &Options,
+    ) -> Result<B256> {
+        let network_prover = self
+            .network_prover()
+            .ok_or_else(|| anyhow::anyhow!("Proofs are only requested from the prover network"))?;
+        let stdin = Self::write_stdin(light_blocks, options);
+
+        network_prover
+            .prove(&self.pkey, &stdin)
+            .mode(self.proof_mode.into())
+            .request_async()
+            .await
+    }
+
+    /// Wait for the proof of a request to the prover network, which may have been made before a
+    /// restart.
+    pub async fn wait_for_proof(&self, request_id: B256) -> Result<SP1ProofWithPublicValues> {
+        let network_prover = self
+            .network_prover()
+            .ok_or_else(|| anyhow::anyhow!("Proofs are only requested from the prover network"))?;
+
+        network_prover.wait_proof(request_id, None).await
+    }
+
+    /// Whether the error of waiting for a proof means the request will never be fulfilled, so the
+    /// proof has to be requested again.
+    pub fn is_failed_request(error: &anyhow::Error) -> bool {
+        matches!(
+            error.downcast_ref::<NetworkError>(),
+            Some(
+                NetworkError::RequestUnexecutable { .. }
+                    | NetworkError::RequestUnfulfillable { .. }
+                    | NetworkError::RequestTimedOut { .. }
+            )
+        )
+    }
+
+    /// Generate a proof of an update from trusted_light_block to target_light_block, verified
+    /// with the given light client options. Returns a proof of the prover's `ProofMode`.
     pub fn generate_tendermint_proof(
         &self,
         trusted_light_block: &LightBlock,
         target_light_block: &LightBlock,
+        options: &Options,
+    ) -> SP1ProofWithPublicValues {
+        self.generate_tendermint_proof_for_path(
+            &[trusted_light_block.clone(), target_light_block.clone()],
+            options,
+        )
+    }
+
+    /// Generate a proof of an update along a path of light blocks, where each light block is
+    /// verified against the previous one with the given light client options. The first light
+    /// block is the trusted block and the last light block is the target block. Only the first and
+    /// last light blocks are committed to the public values. Returns a proof of the prover's
+    /// `ProofMode`.
+    pub fn generate_tendermint_proof_for_path(
+        &self,
+        light_blocks: &[LightBlock],
+        options: &Options,
     ) -> SP1ProofWithPublicValues {
-        // Encode the light blocks to be input into our program.
-        let encoded_1 = serde_cbor::to_vec(&trusted_light_block).unwrap();
-        let encoded_2 = serde_cbor::to_vec(&target_light_block).unwrap();
+        let stdin = Self::write_stdin(light_blocks, options);
+
+        // Generate the proof. Depending on the prover, this may be a mock, local or network proof.
+        self.prove(&stdin).expect("Failed to execute.")
+    }
 
-        // Write the encoded light blocks to stdin.
+    /// Generate a proof that the two conflicting light blocks of the evidence both verify against
+    /// its common light block, reached along its path from the trusted block, with the given light
+    /// client options, which the SP1Tendermint contract accepts as proof of misbehaviour. Returns
+    /// a proof of the prover's `ProofMode`.
+    pub fn generate_misbehaviour_proof(
+        &self,
+        evidence: &LightClientAttackEvidence,
+        options: &Options,
+    ) -> SP1ProofWithPublicValues {
+        // Encode the light blocks and options to be input into our program.
+        let encoded_common_path = serde_cbor::to_vec(&evidence.common_path).unwrap();
+        let encoded_primary = serde_cbor::to_vec(&evidence.primary_light_block).unwrap();
+        let encoded_witness = serde_cbor::to_vec(&evidence.witness_light_block).unwrap();
+        let encoded_options = serde_cbor::to_vec(options).unwrap();
+
+        // Write the mode, the encoded light blocks and options to stdin.
         let mut stdin = SP1Stdin::new();
-        stdin.write_vec(encoded_1);
-        stdin.write_vec(encoded_2);
+        stdin.write(&MODE_MISBEHAVIOUR);
+        stdin.write_vec(encoded_common_path);
+        stdin.write_vec(encoded_primary);
+        stdin.write_vec(encoded_witness);
+        stdin.write_vec(encoded_options);
 
-        // Generate the proof. Depending on SP1_PROVER env variable, this may be a mock, local or network proof.
-        let proof = self
-            .prover_client
-            .prove(&self.pkey, &stdin)
-            .plonk()
-            .run()
-            .expect("Failed to execute.");
+        self.prove(&stdin).expect("Failed to execute.")
+    }
 
-        // Return the proof.
-        proof
+    /// Execute the Tendermint program for an update from trusted_light_block to
+    /// target_light_block without generating a proof. Returns the cycle count, the syscall counts
+    /// and the decoded public values.
+    pub fn execute(
+        &self,
+        trusted_light_block: &LightBlock,
+        target_light_block: &LightBlock,
+        options: &Options,
+    ) -> Result<TendermintExecution> {
+        self.execute_path(
+            &[trusted_light_block.clone(), target_light_block.clone()],
+            options,
+        )
+    }
+
+    /// Execute the Tendermint program for an update along a path of light blocks without
+    /// generating a proof, like `execute`.
+    pub fn execute_path(
+        &self,
+        light_blocks: &[LightBlock],
+        options: &Options,
+    ) -> Result<TendermintExecution> {
+        let stdin = Self::write_stdin(light_blocks, options);
+
+        let (public_values, report) = match &self.prover_client {
+            ProverClientKind::Cpu(prover) => Prover::execute(prover, TENDERMINT_ELF, &stdin)?,
+            ProverClientKind::Cuda(prover) => Prover::execute(prover, TENDERMINT_ELF, &stdin)?,
+            ProverClientKind::Network(prover) => Prover::execute(prover, TENDERMINT_ELF, &stdin)?,
+        };
+
+        let syscall_counts = report
+            .syscall_counts
+            .iter()
+            .filter(|(_, count)| **count > 0)
+            .map(|(syscall, count)| (format!("{:?}", syscall), *count))
+            .collect();
+        let output = TendermintOutput::from_public_values(public_values.as_slice())?;
+
+        Ok(TendermintExecution {
+            cycles: report.total_instruction_count(),
+            syscall_counts,
+            output,
+        })
+    }
+
+    /// Prove the Tendermint program on the inputs in the prover's `ProofMode`.
+    fn prove(&self, stdin: &SP1Stdin) -> Result<SP1ProofWithPublicValues> {
+        let mode = self.proof_mode.into();
+        match &self.prover_client {
+            ProverClientKind::Cpu(prover) => Prover::prove(prover, &self.pkey, stdin, mode),
+            ProverClientKind::Cuda(prover) => Prover::prove(prover, &self.pkey, stdin, mode),
+            ProverClientKind::Network(prover) => Prover::prove(prover, &self.pkey, stdin, mode),
+        }
+    }
+
+    /// Write the inputs of the Tendermint program for an update along a path of light blocks to
+    /// stdin.
+    fn write_stdin(light_blocks: &[LightBlock], options: &Options) -> SP1Stdin {
+        assert!(
+            light_blocks.len() >= 2,
+            "A path must contain at least the trusted and the target light block"
+        );
+
+        // Encode the light blocks and options to be input into our program.
+        let encoded_path = serde_cbor::to_vec(&light_blocks).unwrap();
+        let encoded_options = serde_cbor::to_vec(options).unwrap();
+
+        // Write the mode, the encoded light blocks and options to stdin.
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&MODE_UPDATE);
+        stdin.write_vec(encoded_path);
+        stdin.write_vec(encoded_options);
+        stdin
     }
 }
@@ -0,0 +1,151 @@
+use anyhow::Result;
+use axum::{routing::get, Router};
+use prometheus::{
+    Encoder, GaugeVec, Histogram, HistogramOpts, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+use std::{net::SocketAddr, sync::Arc};
+
+/// The labels of the metrics of a single contract.
+const CONTRACT_LABELS: &[&str] = &["chain_id", "contract"];
+
+/// Prometheus metrics of the operator, served in the text exposition format on `/metrics`. Metrics
+/// of a single contract are labeled by `chain_id` and `contract`.
+#[derive(Clone)]
+pub struct OperatorMetrics {
+    registry: Registry,
+    /// The latest block height of the contract.
+    pub contract_latest_height: IntGaugeVec,
+    /// The latest block height of the Tendermint chain.
+    pub chain_latest_height: IntGauge,
+    /// The number of blocks the contract is behind the chain.
+    pub lag_blocks: IntGaugeVec,
+    /// The number of seconds since the time of the contract's latest block.
+    pub lag_seconds: GaugeVec,
+    /// The time taken to generate a proof of an update, in seconds.
+    pub proof_duration_seconds: Histogram,
+    /// The number of cycles of the latest proven update.
+    pub proof_cycles: IntGauge,
+    /// The gas used by the latest transaction relaying a proof.
+    pub tx_gas_used: IntGaugeVec,
+    /// Failed Tendermint RPC requests, labeled by endpoint.
+    pub rpc_errors: IntCounterVec,
+    /// Failed reads of the contract's state, after which it is skipped until the next iteration.
+    pub contract_read_errors: IntCounterVec,
+    /// The number of seconds until the contract's latest block is older than the trusting
+    /// period, after which the contract can no longer be updated.
+    pub trusting_period_remaining_seconds: GaugeVec,
+}
+
+impl OperatorMetrics {
+    pub fn new() -> Result<Self> {
+        let registry = Registry::new_custom(Some("sp1_tendermint_operator".to_string()), None)?;
+
+        let contract_latest_height = IntGaugeVec::new(
+            Opts::new(
+                "contract_latest_height",
+                "The latest block height of the contract.",
+            ),
+            CONTRACT_LABELS,
+        )?;
+        let chain_latest_height = IntGauge::new(
+            "chain_latest_height",
+            "The latest block height of the Tendermint chain.",
+        )?;
+        let lag_blocks = IntGaugeVec::new(
+            Opts::new(
+                "lag_blocks",
+                "The number of blocks the contract is behind the chain.",
+            ),
+            CONTRACT_LABELS,
+        )?;
+        let lag_seconds = GaugeVec::new(
+            Opts::new(
+                "lag_seconds",
+                "The number of seconds since the time of the contract's latest block.",
+            ),
+            CONTRACT_LABELS,
+        )?;
+        let proof_duration_seconds = Histogram::with_opts(
+            HistogramOpts::new(
+                "proof_duration_seconds",
+                "The time taken to generate a proof of an update, in seconds.",
+            )
+            .buckets(vec![
+                30.0, 60.0, 120.0, 300.0, 600.0, 1200.0, 1800.0, 3600.0,
+            ]),
+        )?;
+        let proof_cycles = IntGauge::new(
+            "proof_cycles",
+            "The number of cycles of the latest proven update, if REPORT_CYCLES is set.",
+        )?;
+        let tx_gas_used = IntGaugeVec::new(
+            Opts::new(
+                "tx_gas_used",
+                "The gas used by the latest transaction relaying a proof.",
+            ),
+            CONTRACT_LABELS,
+        )?;
+        let rpc_errors = IntCounterVec::new(
+            Opts::new("rpc_errors_total", "Failed Tendermint RPC requests."),
+            &["endpoint"],
+        )?;
+        let contract_read_errors = IntCounterVec::new(
+            Opts::new(
+                "contract_read_errors_total",
+                "Failed reads of the contract's state.",
+            ),
+            CONTRACT_LABELS,
+        )?;
+        let trusting_period_remaining_seconds = GaugeVec::new(
+            Opts::new(
+                "trusting_period_remaining_seconds",
+                "The number of seconds until the contract's latest block is older than the trusting period.",
+            ),
+            CONTRACT_LABELS,
+        )?;
+
+        registry.register(Box::new(contract_latest_height.clone()))?;
+        registry.register(Box::new(chain_latest_height.clone()))?;
+        registry.register(Box::new(lag_blocks.clone()))?;
+        registry.register(Box::new(lag_seconds.clone()))?;
+        registry.register(Box::new(proof_duration_seconds.clone()))?;
+        registry.register(Box::new(proof_cycles.clone()))?;
+        registry.register(Box::new(tx_gas_used.clone()))?;
+        registry.register(Box::new(rpc_errors.clone()))?;
+        registry.register(Box::new(contract_read_errors.clone()))?;
+        registry.register(Box::new(trusting_period_remaining_seconds.clone()))?;
+
+        Ok(Self {
+            registry,
+            contract_latest_height,
+            chain_latest_height,
+            lag_blocks,
+            lag_seconds,
+            proof_duration_seconds,
+            proof_cycles,
+            tx_gas_used,
+            rpc_errors,
+            contract_read_errors,
+            trusting_period_remaining_seconds,
+        })
+    }
+
+    /// Encodes the metrics in the Prometheus text exposition format.
+    pub fn encode(&self) -> Result<String> {
+        let mut buffer = vec![];
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8(buffer)?)
+    }
+
+    /// Serves the metrics on `/metrics` at the given address until the server fails.
+    pub async fn serve(self: Arc<Self>, addr: SocketAddr) -> Result<()> {
+        let app = Router::new().route(
+            "/metrics",
+            get(move || async move { self.encode().unwrap_or_else(|e| e.to_string()) }),
+        );
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        axum::serve(listener, app).await?;
+        Ok(())
+    }
+}
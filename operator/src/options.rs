@@ -0,0 +1,65 @@
+use anyhow::{anyhow, Result};
+use clap::Args;
+use serde::{Deserialize, Deserializer};
+use std::time::Duration;
+use tendermint_light_client_verifier::{options::Options, types::TrustThreshold};
+
+/// The default trusting period of 2 weeks.
+pub const DEFAULT_TRUSTING_PERIOD_SECS: u64 = 14 * 24 * 60 * 60;
+
+/// Light client options used to verify an update, shared by the binaries.
+#[derive(Args, Debug, Clone, Deserialize)]
+#[clap(about = None, long_about = None)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrustOptionsArgs {
+    /// Fraction of the trusted validator set's voting power that must sign the target block.
+    #[clap(long, env, default_value = "2/3", value_parser = parse_trust_threshold)]
+    #[serde(deserialize_with = "deserialize_trust_threshold")]
+    pub trust_threshold: TrustThreshold,
+
+    /// How long a validator set is trusted for, in seconds. Must be shorter than the chain's
+    /// unbonding period.
+    #[clap(long, env, default_value_t = DEFAULT_TRUSTING_PERIOD_SECS)]
+    pub trusting_period: u64,
+
+    /// Maximum amount the local clock may drift behind a block timestamp, in seconds.
+    #[clap(long, env, default_value_t = 0)]
+    pub clock_drift: u64,
+}
+
+impl Default for TrustOptionsArgs {
+    fn default() -> Self {
+        Self {
+            trust_threshold: TrustThreshold::TWO_THIRDS,
+            trusting_period: DEFAULT_TRUSTING_PERIOD_SECS,
+            clock_drift: 0,
+        }
+    }
+}
+
+impl From<TrustOptionsArgs> for Options {
+    fn from(args: TrustOptionsArgs) -> Self {
+        Options {
+            trust_threshold: args.trust_threshold,
+            trusting_period: Duration::from_secs(args.trusting_period),
+            clock_drift: Duration::from_secs(args.clock_drift),
+        }
+    }
+}
+
+/// Parses a trust threshold of the form `numerator/denominator`, e.g. `2/3`.
+pub fn parse_trust_threshold(s: &str) -> Result<TrustThreshold> {
+    let (numerator, denominator) = s
+        .split_once('/')
+        .ok_or_else(|| anyhow!("trust threshold must be of the form numerator/denominator"))?;
+    TrustThreshold::new(numerator.trim().parse()?, denominator.trim().parse()?)
+        .map_err(|e| anyhow!("invalid trust threshold {}: {}", s, e))
+}
+
+/// Deserializes a trust threshold of the form `numerator/denominator`.
+fn deserialize_trust_threshold<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<TrustThreshold, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    parse_trust_threshold(&s).map_err(serde::de::Error::custom)
+}
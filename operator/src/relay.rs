@@ -0,0 +1,88 @@
+use crate::{bindings::SP1TendermintClient, contract::ContractClient, fees::FeeArgs};
+use alloy::network::EthereumWallet;
+use alloy_primitives::{Address, B256};
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::{collections::BTreeMap, str::FromStr};
+
+/// An `SP1Tendermint` contract on an EVM chain that the operator relays proofs to.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RelayTarget {
+    pub chain_id: u64,
+    pub contract: Address,
+    pub rpc_url: String,
+}
+
+impl RelayTarget {
+    /// Reads the single target at `CHAIN_ID`, `RPC_URL` and `CONTRACT_ADDRESS` from the
+    /// environment, looking each variable up with `env`.
+    pub fn from_env(env: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        Ok(Self {
+            chain_id: env("CHAIN_ID")
+                .context("CHAIN_ID not set")?
+                .parse()
+                .context("CHAIN_ID not a valid u64")?,
+            contract: env("CONTRACT_ADDRESS")
+                .context("CONTRACT_ADDRESS not set")?
+                .parse()
+                .context("CONTRACT_ADDRESS not a valid address")?,
+            rpc_url: env("RPC_URL").context("RPC_URL not set")?,
+        })
+    }
+
+    /// Creates a client of the target's contract sending transactions signed by the wallet.
+    pub fn client(&self, wallet: EthereumWallet, fees: FeeArgs) -> Result<SP1TendermintClient> {
+        let contract_client =
+            ContractClient::new(self.chain_id, &self.rpc_url, wallet, self.contract)?
+                .with_fee_args(fees);
+        Ok(SP1TendermintClient::new(contract_client))
+    }
+}
+
+impl FromStr for RelayTarget {
+    type Err = anyhow::Error;
+
+    /// Parses `CHAIN_ID:CONTRACT_ADDRESS:RPC_URL`, e.g.
+    /// `11155111:0x5FbDB2315678afecb367f032d93F642f64180aa3:https://ethereum-sepolia.publicnode.com/`.
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.trim().splitn(3, ':');
+        let (Some(chain_id), Some(contract), Some(rpc_url)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(anyhow!(
+                "relay target must be of the form CHAIN_ID:CONTRACT_ADDRESS:RPC_URL"
+            ));
+        };
+
+        Ok(Self {
+            chain_id: chain_id
+                .parse()
+                .with_context(|| format!("invalid chain ID {}", chain_id))?,
+            contract: contract
+                .parse()
+                .with_context(|| format!("invalid contract address {}", contract))?,
+            rpc_url: rpc_url.to_string(),
+        })
+    }
+}
+
+/// The latest block of a light client, which is the trusted block of the next update to it. A
+/// proof of an update is accepted by every contract with the same trusted state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TrustedState {
+    pub height: u64,
+    pub header: B256,
+}
+
+/// Groups contracts by their trusted state, so a single proof can be relayed to every contract of a
+/// group. Groups are ordered by ascending height, so the contracts furthest behind come first.
+pub fn group_by_trusted_state<T>(
+    contracts: impl IntoIterator<Item = (T, TrustedState)>,
+) -> BTreeMap<TrustedState, Vec<T>> {
+    let mut groups = BTreeMap::<_, Vec<_>>::new();
+    for (contract, trusted_state) in contracts {
+        groups.entry(trusted_state).or_default().push(contract);
+    }
+    groups
+}
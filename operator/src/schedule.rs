@@ -0,0 +1,180 @@
+use anyhow::{anyhow, bail, Result};
+use serde::Deserialize;
+use std::{fmt, str::FromStr, time::Duration};
+
+const DEFAULT_POLL_INTERVAL: u64 = 60;
+const DEFAULT_MIN_BLOCK_STEP: u64 = 1;
+
+/// How the operator picks the target block of an update from the chain's latest block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum TargetPolicy {
+    /// Target the latest block.
+    Latest,
+    /// Target the block the given number of confirmations behind the latest block.
+    Confirmations(u64),
+    /// Target the latest block whose height is a multiple of the given number.
+    Aligned(u64),
+}
+
+impl TargetPolicy {
+    /// The latest height the policy allows targeting, given the chain's latest block.
+    pub fn target(&self, chain_latest_height: u64) -> u64 {
+        match *self {
+            TargetPolicy::Latest => chain_latest_height,
+            TargetPolicy::Confirmations(n) => chain_latest_height.saturating_sub(n),
+            TargetPolicy::Aligned(n) => chain_latest_height - chain_latest_height % n,
+        }
+    }
+
+    /// The highest height at or below the cap that the policy allows. Unlike `target`, the cap is
+    /// not the chain's latest block, so no confirmations are subtracted.
+    fn target_capped(&self, max_height: u64) -> u64 {
+        match *self {
+            TargetPolicy::Aligned(n) => max_height - max_height % n,
+            _ => max_height,
+        }
+    }
+}
+
+impl FromStr for TargetPolicy {
+    type Err = anyhow::Error;
+
+    /// Parses `latest`, `confirmations:N` or `aligned:N`.
+    fn from_str(s: &str) -> Result<Self> {
+        let (policy, n) = match s.split_once(':') {
+            Some((policy, n)) => (policy, Some(n.trim().parse::<u64>()?)),
+            None => (s, None),
+        };
+        match (policy.trim(), n) {
+            ("latest", None) => Ok(TargetPolicy::Latest),
+            ("confirmations", Some(n)) => Ok(TargetPolicy::Confirmations(n)),
+            ("aligned", Some(0)) => bail!("alignment must be greater than 0"),
+            ("aligned", Some(n)) => Ok(TargetPolicy::Aligned(n)),
+            _ => Err(anyhow!(
+                "target policy must be latest, confirmations:N or aligned:N"
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for TargetPolicy {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for TargetPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TargetPolicy::Latest => write!(f, "latest"),
+            TargetPolicy::Confirmations(n) => write!(f, "confirmations:{}", n),
+            TargetPolicy::Aligned(n) => write!(f, "aligned:{}", n),
+        }
+    }
+}
+
+/// When the operator updates the contract and which block it updates it to.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScheduleArgs {
+    /// Seconds to wait between iterations of the operator loop.
+    pub poll_interval: u64,
+
+    /// Maximum number of blocks a single update may advance the contract by. Unlimited if unset.
+    pub max_block_step: Option<u64>,
+
+    /// Minimum number of blocks an update must advance the contract by to be worth proving.
+    pub min_block_step: u64,
+
+    /// How to pick the target block from the latest block: `latest`, `confirmations:N` to stay N
+    /// blocks behind the latest block, or `aligned:N` to only target multiples of N.
+    pub target_policy: TargetPolicy,
+}
+
+impl Default for ScheduleArgs {
+    fn default() -> Self {
+        Self {
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            max_block_step: None,
+            min_block_step: DEFAULT_MIN_BLOCK_STEP,
+            target_policy: TargetPolicy::Latest,
+        }
+    }
+}
+
+impl ScheduleArgs {
+    /// Checks that the steps and the target policy are consistent.
+    pub fn validate(&self) -> Result<()> {
+        if self.poll_interval == 0 {
+            bail!("poll interval must be greater than 0");
+        }
+        if self.min_block_step == 0 {
+            bail!("min block step must be greater than 0");
+        }
+        if let Some(max_block_step) = self.max_block_step {
+            if max_block_step < self.min_block_step {
+                bail!(
+                    "max block step {} is less than min block step {}",
+                    max_block_step,
+                    self.min_block_step
+                );
+            }
+            if let TargetPolicy::Aligned(n) = self.target_policy {
+                if max_block_step < n {
+                    bail!(
+                        "max block step {} is less than the alignment {}, so no aligned block is reachable",
+                        max_block_step,
+                        n
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.poll_interval)
+    }
+
+    /// Selects the target block of the next update from the trusted block, or `None` if no block
+    /// allowed by the target policy is at least `min_block_step` blocks ahead of the trusted block.
+    /// The target is capped at `max_block_step` blocks ahead, keeping it aligned under the `aligned`
+    /// policy.
+    pub fn select_target(&self, trusted_height: u64, chain_latest_height: u64) -> Option<u64> {
+        let mut target_height = self.target_policy.target(chain_latest_height);
+        if let Some(max_block_step) = self.max_block_step {
+            let max_height = trusted_height.saturating_add(max_block_step);
+            if target_height > max_height {
+                target_height = self.target_policy.target_capped(max_height);
+            }
+        }
+
+        if target_height < trusted_height.saturating_add(self.min_block_step) {
+            return None;
+        }
+        Some(target_height)
+    }
+
+    /// Selects the target block of an update that must be proven immediately, e.g. because the
+    /// trusted block is about to expire. Like `select_target`, but ignores `min_block_step` and
+    /// falls back to the latest block if the target policy allows no block after the trusted block.
+    /// Returns `None` only if the chain has not advanced past the trusted block.
+    pub fn select_urgent_target(
+        &self,
+        trusted_height: u64,
+        chain_latest_height: u64,
+    ) -> Option<u64> {
+        let mut target_height = self.target_policy.target(chain_latest_height);
+        if target_height <= trusted_height {
+            target_height = chain_latest_height;
+        }
+        if let Some(max_block_step) = self.max_block_step {
+            target_height = target_height.min(trusted_height.saturating_add(max_block_step));
+        }
+
+        (target_height > trusted_height).then_some(target_height)
+    }
+}
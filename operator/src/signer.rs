@@ -0,0 +1,244 @@
+use crate::config::env_var;
+use alloy::{
+    consensus::SignableTransaction,
+    network::{EthereumWallet, TxSigner},
+    signers::local::PrivateKeySigner,
+};
+use alloy_primitives::{Address, Bytes, PrimitiveSignature, B256};
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Where the password of an encrypted JSON keystore is read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeystorePassword {
+    /// The first line of the given file.
+    File(PathBuf),
+    /// A prompt on the terminal, without echoing the input.
+    Prompt,
+}
+
+/// The key the operator signs its transactions with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignerConfig {
+    /// A raw hex private key.
+    PrivateKey(String),
+    /// An encrypted JSON keystore, as written by geth or `cast wallet`.
+    Keystore {
+        path: PathBuf,
+        password: KeystorePassword,
+    },
+    /// A `RemoteSigner` holding the key of the given address.
+    Remote { url: String, address: Address },
+}
+
+impl SignerConfig {
+    /// Reads the signer from the environment, looked up with `env`. `KEYSTORE_PATH`, with the
+    /// password in `KEYSTORE_PASSWORD_FILE` or prompted for, takes precedence over
+    /// `REMOTE_SIGNER_URL` and `REMOTE_SIGNER_ADDRESS`, which take precedence over a plaintext
+    /// `PRIVATE_KEY`. Variables left blank are ignored.
+    pub fn from_env(env: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        if let Some(path) = env_var::<PathBuf>(&env, "KEYSTORE_PATH")? {
+            let password = match env_var::<PathBuf>(&env, "KEYSTORE_PASSWORD_FILE")? {
+                Some(password_file) => KeystorePassword::File(password_file),
+                None => KeystorePassword::Prompt,
+            };
+            return Ok(SignerConfig::Keystore { path, password });
+        }
+        if let Some(url) = env_var::<String>(&env, "REMOTE_SIGNER_URL")? {
+            let address =
+                env_var(&env, "REMOTE_SIGNER_ADDRESS")?.context("REMOTE_SIGNER_ADDRESS not set")?;
+            return Ok(SignerConfig::Remote { url, address });
+        }
+        let private_key = env_var::<String>(&env, "PRIVATE_KEY")?
+            .context("None of KEYSTORE_PATH, REMOTE_SIGNER_URL or PRIVATE_KEY set")?;
+        Ok(SignerConfig::PrivateKey(private_key))
+    }
+
+    /// The address of the configured key. A keystore is not decrypted, so no password is
+    /// prompted for, and its address is read from its `address` field instead.
+    pub fn address(&self) -> Result<Address> {
+        match self {
+            SignerConfig::PrivateKey(private_key) => Ok(private_key_signer(private_key)?.address()),
+            SignerConfig::Keystore { path, .. } => {
+                #[derive(Deserialize)]
+                struct KeystoreAddress {
+                    address: Option<Address>,
+                }
+
+                let contents = fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read keystore {}", path.display()))?;
+                let keystore: KeystoreAddress = serde_json::from_str(&contents)
+                    .with_context(|| format!("Failed to parse keystore {}", path.display()))?;
+                keystore.address.with_context(|| {
+                    format!("Keystore {} does not contain its address", path.display())
+                })
+            }
+            SignerConfig::Remote { address, .. } => Ok(*address),
+        }
+    }
+
+    /// Creates a wallet signing transactions with the configured key. The wallet signs for any
+    /// chain, so a single wallet, and a single keystore password prompt, serves every relay target.
+    pub fn wallet(&self) -> Result<EthereumWallet> {
+        let signer = match self {
+            SignerConfig::PrivateKey(private_key) => private_key_signer(private_key)?,
+            SignerConfig::Keystore { path, password } => {
+                let password = password.read(path)?;
+                PrivateKeySigner::decrypt_keystore(path, password)
+                    .with_context(|| format!("Failed to decrypt keystore {}", path.display()))?
+            }
+            SignerConfig::Remote { url, address } => {
+                return Ok(EthereumWallet::from(RemoteSigner::new(url, *address)));
+            }
+        };
+
+        Ok(EthereumWallet::from(signer))
+    }
+}
+
+/// Parses a raw hex private key.
+fn private_key_signer(private_key: &str) -> Result<PrivateKeySigner> {
+    // Strip the `0x` prefix from the private key (if present).
+    let private_key = private_key.strip_prefix("0x").unwrap_or(private_key);
+    Ok(private_key.parse::<PrivateKeySigner>()?)
+}
+
+impl KeystorePassword {
+    /// Reads the password of the keystore at the given path.
+    fn read(&self, keystore_path: &Path) -> Result<String> {
+        match self {
+            KeystorePassword::File(path) => {
+                let contents = fs::read_to_string(path).with_context(|| {
+                    format!("Failed to read keystore password file {}", path.display())
+                })?;
+                Ok(contents.lines().next().unwrap_or_default().to_string())
+            }
+            KeystorePassword::Prompt => rpassword::prompt_password(format!(
+                "Password for keystore {}: ",
+                keystore_path.display()
+            ))
+            .context("Failed to read the keystore password"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SignRequest {
+    address: Address,
+    hash: B256,
+}
+
+#[derive(Deserialize)]
+struct SignResponse {
+    signature: Bytes,
+}
+
+/// A signer that has a local HTTP signing service sign transactions, so the key never leaves the
+/// service. The service signs a 32-byte hash with the key of the given address:
+///
+/// ```text
+/// POST {url}/sign {"address": "0x…", "hash": "0x…"}
+/// 200 {"signature": "0x…"}
+/// ```
+///
+/// where the signature is the 65-byte concatenation of r, s and v.
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    url: String,
+    address: Address,
+    client: Client,
+}
+
+impl RemoteSigner {
+    pub fn new(url: &str, address: Address) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            address,
+            client: Client::new(),
+        }
+    }
+
+    /// Has the service sign the hash, and checks that the signature is by the signer's address.
+    pub async fn sign_hash(&self, hash: &B256) -> Result<PrimitiveSignature> {
+        let response = self
+            .client
+            .post(format!("{}/sign", self.url))
+            .json(&SignRequest {
+                address: self.address,
+                hash: *hash,
+            })
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            bail!(
+                "Remote signer returned status {}: {}",
+                response.status(),
+                response.text().await.unwrap_or_default()
+            );
+        }
+        let signature = response.json::<SignResponse>().await?.signature;
+        let signature = PrimitiveSignature::try_from(signature.as_ref())?;
+
+        let signer = signature.recover_address_from_prehash(hash)?;
+        if signer != self.address {
+            bail!(
+                "Remote signer signed with address {} instead of {}",
+                signer,
+                self.address
+            );
+        }
+        Ok(signature)
+    }
+}
+
+#[async_trait]
+impl TxSigner<PrimitiveSignature> for RemoteSigner {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    async fn sign_transaction(
+        &self,
+        tx: &mut dyn SignableTransaction<PrimitiveSignature>,
+    ) -> alloy::signers::Result<PrimitiveSignature> {
+        self.sign_hash(&tx.signature_hash())
+            .await
+            .map_err(alloy::signers::Error::other)
+    }
+}
+
+/// A signer that only knows its address and refuses to sign, so that transactions from the address
+/// can be simulated without its key.
+#[derive(Debug, Clone)]
+pub struct WatchOnlySigner {
+    address: Address,
+}
+
+impl WatchOnlySigner {
+    pub fn new(address: Address) -> Self {
+        Self { address }
+    }
+}
+
+#[async_trait]
+impl TxSigner<PrimitiveSignature> for WatchOnlySigner {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    async fn sign_transaction(
+        &self,
+        _tx: &mut dyn SignableTransaction<PrimitiveSignature>,
+    ) -> alloy::signers::Result<PrimitiveSignature> {
+        Err(alloy::signers::Error::other(format!(
+            "{} is watch-only and can't sign transactions",
+            self.address
+        )))
+    }
+}
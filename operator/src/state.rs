@@ -0,0 +1,227 @@
+use alloy_primitives::{Address, Bytes, B256};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// An update the operator started proving. A proof generated locally can't be recovered after a
+/// crash, but the job is resumed with the same target block so the work isn't redone for a
+/// different one. A proof requested from the prover network is waited for again instead.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvingJob {
+    pub trusted_height: u64,
+    pub target_height: u64,
+    /// Unix timestamp in seconds at which proving started.
+    pub started_at: u64,
+    /// The ID of the prover network request for the proof, once it was requested.
+    pub request_id: Option<B256>,
+}
+
+/// A generated proof of an update that has not been submitted to every contract it starts at yet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletedProof {
+    pub trusted_height: u64,
+    pub target_height: u64,
+    pub public_values: Bytes,
+    pub proof: Bytes,
+}
+
+/// A transaction relaying a proof that was sent to the contract but whose receipt has not been
+/// seen yet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingTransaction {
+    pub tx_hash: B256,
+    /// The chain and the contract the transaction was sent to.
+    pub chain_id: u64,
+    pub contract: Address,
+    pub trusted_height: u64,
+    pub target_height: u64,
+    /// Unix timestamp in seconds at which the transaction was sent.
+    pub sent_at: u64,
+}
+
+/// The operator's progress, persisted so that it can resume after a restart.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperatorState {
+    pub in_flight_job: Option<ProvingJob>,
+    pub completed_proofs: Vec<CompletedProof>,
+    pub pending_transactions: Vec<PendingTransaction>,
+}
+
+/// A JSON file backed store of the `OperatorState`. Every mutation is written to disk before it
+/// returns.
+#[derive(Debug)]
+pub struct StateStore {
+    path: PathBuf,
+    state: OperatorState,
+}
+
+impl StateStore {
+    /// Loads the state from the given file, starting from an empty state if it doesn't exist.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let state = match fs::read(&path) {
+            Ok(contents) => serde_json::from_slice(&contents)
+                .with_context(|| format!("Failed to parse state file {}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => OperatorState::default(),
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to read state file {}", path.display()))
+            }
+        };
+
+        Ok(Self { path, state })
+    }
+
+    /// The path of the state file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn state(&self) -> &OperatorState {
+        &self.state
+    }
+
+    /// Records that proving the update from the trusted block to the target block started.
+    pub fn start_job(&mut self, trusted_height: u64, target_height: u64) -> Result<()> {
+        self.state.in_flight_job = Some(ProvingJob {
+            trusted_height,
+            target_height,
+            started_at: unix_timestamp(),
+            request_id: None,
+        });
+        self.save()
+    }
+
+    /// Records the prover network request for the proof of the in-flight job, so a restart waits
+    /// for it rather than proving the update again.
+    pub fn record_request(&mut self, request_id: B256) -> Result<()> {
+        if let Some(job) = &mut self.state.in_flight_job {
+            job.request_id = Some(request_id);
+        }
+        self.save()
+    }
+
+    /// Forgets the prover network request of the in-flight job, e.g. if it can't be fulfilled, so
+    /// the proof is requested again.
+    pub fn clear_request(&mut self) -> Result<()> {
+        if let Some(job) = &mut self.state.in_flight_job {
+            job.request_id = None;
+        }
+        self.save()
+    }
+
+    /// Replaces the in-flight job with its completed proof.
+    pub fn complete_job(&mut self, proof: CompletedProof) -> Result<()> {
+        self.state.in_flight_job = None;
+        self.state.completed_proofs.push(proof);
+        self.save()
+    }
+
+    /// Drops the in-flight job, e.g. if proving failed.
+    pub fn abandon_job(&mut self) -> Result<()> {
+        self.state.in_flight_job = None;
+        self.save()
+    }
+
+    /// Records the transaction that submitted the completed proof of the update to the contract.
+    /// The proof is kept until it is discarded, since it may still have to be submitted to other
+    /// contracts.
+    pub fn submit_proof(
+        &mut self,
+        trusted_height: u64,
+        target_height: u64,
+        chain_id: u64,
+        contract: Address,
+        tx_hash: B256,
+    ) -> Result<()> {
+        self.state.pending_transactions.push(PendingTransaction {
+            tx_hash,
+            chain_id,
+            contract,
+            trusted_height,
+            target_height,
+            sent_at: unix_timestamp(),
+        });
+        self.save()
+    }
+
+    /// Removes the completed proof of the update, e.g. because it was submitted to every contract or
+    /// the contracts can no longer accept it.
+    pub fn discard_proof(&mut self, trusted_height: u64, target_height: u64) -> Result<()> {
+        self.state
+            .completed_proofs
+            .retain(|p| (p.trusted_height, p.target_height) != (trusted_height, target_height));
+        self.save()
+    }
+
+    /// Records that a pending transaction was replaced by a transaction with the same nonce and
+    /// bumped fees.
+    pub fn replace_transaction(&mut self, tx_hash: B256, replacement_tx_hash: B256) -> Result<()> {
+        if let Some(tx) = self
+            .state
+            .pending_transactions
+            .iter_mut()
+            .find(|tx| tx.tx_hash == tx_hash)
+        {
+            tx.tx_hash = replacement_tx_hash;
+            tx.sent_at = unix_timestamp();
+        }
+        self.save()
+    }
+
+    /// Removes a pending transaction once its receipt was seen or it was dropped.
+    pub fn resolve_transaction(&mut self, tx_hash: B256) -> Result<()> {
+        self.state
+            .pending_transactions
+            .retain(|tx| tx.tx_hash != tx_hash);
+        self.save()
+    }
+
+    /// Removes the in-flight job and completed proofs that can no longer be submitted, because they
+    /// don't start at any contract's latest height. Returns the number of entries removed.
+    pub fn prune_stale(&mut self, contract_latest_heights: &[u64]) -> Result<usize> {
+        let mut removed = 0;
+        if let Some(job) = &self.state.in_flight_job {
+            if !contract_latest_heights.contains(&job.trusted_height) {
+                self.state.in_flight_job = None;
+                removed += 1;
+            }
+        }
+        let proofs = self.state.completed_proofs.len();
+        self.state
+            .completed_proofs
+            .retain(|p| contract_latest_heights.contains(&p.trusted_height));
+        removed += proofs - self.state.completed_proofs.len();
+
+        if removed > 0 {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    /// Writes the state to a temporary file and renames it over the state file, so a crash never
+    /// leaves a partially written state behind.
+    fn save(&self) -> Result<()> {
+        let tmp_path = self.path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_vec_pretty(&self.state)?)
+            .with_context(|| format!("Failed to write state file {}", tmp_path.display()))?;
+        fs::rename(&tmp_path, &self.path)
+            .with_context(|| format!("Failed to write state file {}", self.path.display()))?;
+        Ok(())
+    }
+}
+
+fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
@@ -5,6 +5,20 @@ use tendermint::{
     Block,
 };
 
+#[derive(Debug, Deserialize)]
+pub struct RpcErrorResponse {
+    pub error: RpcErrorBody,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Deserialize)]
+pub struct RpcErrorBody {
+    pub code: i64,
+    pub message: String,
+    #[serde(default)]
+    pub data: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct PeerIdResponse {
     pub result: PeerIdWrapper,
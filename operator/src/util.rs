@@ -1,81 +1,429 @@
 #![allow(dead_code)]
 use crate::types::*;
-use anyhow::Result;
+use log::{info, warn};
+use prometheus::IntCounterVec;
 use reqwest::Client;
-use std::{collections::HashMap, env};
+use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    env,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
 use subtle_encoding::hex;
 use tendermint::{
     block::signed_header::SignedHeader,
+    chain,
     node::Id,
     validator::{Info, Set},
+    Hash,
 };
 use tendermint_light_client_verifier::types::{LightBlock, ValidatorSet};
+use thiserror::Error;
 
-pub struct TendermintRPCClient {
-    url: String,
-    client: Client,
+/// Errors returned by the `TendermintRPCClient`.
+#[derive(Debug, Error)]
+pub enum TendermintRpcError {
+    /// The HTTP request failed or its body could not be read.
+    #[error("HTTP request to {url} failed: {source}")]
+    Http {
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    /// The response body is not valid JSON for the expected type.
+    #[error("failed to decode response from {url}: {source}")]
+    JsonDecode {
+        url: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    /// The node returned an error or a response with unexpected contents.
+    #[error("malformed response from {url}: {reason}")]
+    MalformedResponse { url: String, reason: String },
+    /// The node does not have the requested height, e.g. because it was pruned.
+    #[error("height {height} is not available on {url}: {reason}")]
+    MissingHeight {
+        url: String,
+        height: u64,
+        reason: String,
+    },
+    /// The node reported a peer ID that is not a 20 byte hex string.
+    #[error("invalid peer ID {0}")]
+    InvalidPeerId(String),
+}
+
+/// A witness endpoint reported a different header than the primary endpoint at the same height,
+/// which indicates a fork or a faulty primary.
+#[derive(Debug, Error)]
+#[error("witness {witness_url} reported header {witness_hash} at height {height}, but the primary reported header {primary_hash}")]
+pub struct Divergence {
+    pub height: u64,
+    pub primary_hash: Hash,
+    pub witness_url: String,
+    pub witness_hash: Hash,
+}
+
+/// The light blocks fetched from the primary endpoints could not be confirmed by the witnesses.
+#[derive(Debug, Error)]
+pub enum WitnessError {
+    /// A witness reported a different header than the primary.
+    #[error(transparent)]
+    Divergence(#[from] Divergence),
+    /// Too few witnesses could be reached to confirm the header, so the primary may be serving a
+    /// forked header that the unreachable witnesses would have disagreed with.
+    #[error(
+        "block {height} was confirmed by {matches} witnesses, but at least {required} are required"
+    )]
+    Unconfirmed {
+        height: u64,
+        matches: usize,
+        required: usize,
+    },
+}
+
+impl TendermintRpcError {
+    /// Whether retrying the request on the same endpoint may succeed.
+    fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            TendermintRpcError::Http { .. }
+                | TendermintRpcError::JsonDecode { .. }
+                | TendermintRpcError::MalformedResponse { .. }
+        )
+    }
+}
+
+/// How requests to the Tendermint RPC endpoints are retried.
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    /// The number of attempts on an endpoint before rotating to the next endpoint.
+    pub max_attempts: u32,
+    /// The delay before the first retry on an endpoint.
+    pub initial_backoff: Duration,
+    /// The maximum delay between retries on an endpoint.
+    pub max_backoff: Duration,
+    /// The factor the delay is multiplied by after each retry.
+    pub backoff_multiplier: u32,
 }
 
-impl Default for TendermintRPCClient {
+impl Default for RetryConfig {
     fn default() -> Self {
-        Self::new(env::var("TENDERMINT_RPC_URL").expect("TENDERMINT_RPC_URL not set"))
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            backoff_multiplier: 2,
+        }
     }
 }
 
+/// A client for a list of Tendermint RPC endpoints serving the same chain. Requests are retried
+/// with exponential backoff, and rotate to the next endpoint when an endpoint keeps failing.
+///
+/// Headers fetched from these primary endpoints can be cross-checked against independent witness
+/// endpoints, following the Tendermint light client attack detector.
+pub struct TendermintRPCClient {
+    urls: Vec<String>,
+    current: AtomicUsize,
+    retry_config: RetryConfig,
+    witnesses: Vec<TendermintRPCClient>,
+    min_witness_matches: usize,
+    error_counter: Option<IntCounterVec>,
+    client: Client,
+}
+
+/// Splits a comma-separated list of URLs.
+pub(crate) fn split_urls(urls: &str) -> Vec<String> {
+    urls.split(',')
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty())
+        .collect()
+}
+
+/// The SHA-256 hash of the chain ID, as committed by the Tendermint program and stored in the
+/// contract.
+pub fn chain_id_hash(chain_id: &chain::Id) -> [u8; 32] {
+    Sha256::digest(chain_id.as_bytes()).into()
+}
+
 impl TendermintRPCClient {
-    pub fn new(url: String) -> Self {
+    /// Creates a new `TendermintRPCClient` for the given endpoints, in order of preference.
+    pub fn new(urls: Vec<String>) -> anyhow::Result<Self> {
+        if urls.is_empty() {
+            anyhow::bail!("At least one Tendermint RPC URL is required");
+        }
+        Ok(Self::for_urls(urls))
+    }
+
+    /// Creates a client for the given non-empty list of endpoints.
+    fn for_urls(urls: Vec<String>) -> Self {
         TendermintRPCClient {
-            url,
+            urls,
+            current: AtomicUsize::new(0),
+            retry_config: RetryConfig::default(),
+            witnesses: vec![],
+            min_witness_matches: 1,
+            error_counter: None,
             client: Client::new(),
         }
     }
 
+    /// Creates a client for the endpoints in `TENDERMINT_RPC_URL`, cross-checked against the
+    /// witnesses in `TENDERMINT_WITNESS_RPC_URLS` if set.
+    pub fn from_env() -> anyhow::Result<Self> {
+        let urls = split_urls(&env::var("TENDERMINT_RPC_URL").unwrap_or_default());
+        if urls.is_empty() {
+            anyhow::bail!("TENDERMINT_RPC_URL not set");
+        }
+        let witness_urls = split_urls(&env::var("TENDERMINT_WITNESS_RPC_URLS").unwrap_or_default());
+
+        Ok(Self::new(urls)?.with_witnesses(witness_urls))
+    }
+
+    /// Sets how failed requests are retried, on both the primary and the witness endpoints.
+    pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+        for witness in &mut self.witnesses {
+            witness.retry_config = retry_config.clone();
+        }
+        self.retry_config = retry_config;
+        self
+    }
+
+    /// Sets the witness endpoints that headers are cross-checked against. Each witness should be
+    /// an independent node of the same chain.
+    pub fn with_witnesses(mut self, witness_urls: Vec<String>) -> Self {
+        self.witnesses = witness_urls
+            .into_iter()
+            .map(|url| {
+                let mut witness = TendermintRPCClient::for_urls(vec![url]);
+                witness.retry_config = self.retry_config.clone();
+                witness.error_counter = self.error_counter.clone();
+                witness
+            })
+            .collect();
+        self
+    }
+
+    /// Counts failed requests in the given counter, labeled by endpoint, on both the primary and the
+    /// witness endpoints.
+    pub fn with_error_counter(mut self, error_counter: IntCounterVec) -> Self {
+        for witness in &mut self.witnesses {
+            witness.error_counter = Some(error_counter.clone());
+        }
+        self.error_counter = Some(error_counter);
+        self
+    }
+
+    /// Sets the minimum number of witnesses that must confirm each header. Defaults to 1. Has no
+    /// effect without witnesses.
+    pub fn with_min_witness_matches(mut self, min_witness_matches: usize) -> Self {
+        self.min_witness_matches = min_witness_matches;
+        self
+    }
+
+    /// The witness endpoints that headers are cross-checked against.
+    pub fn witnesses(&self) -> &[TendermintRPCClient] {
+        &self.witnesses
+    }
+
+    /// Cross-checks the headers of light blocks fetched from the primary endpoints, e.g. every
+    /// block of an update's path after the trusted block, against the witnesses.
+    pub async fn check_witnesses(&self, light_blocks: &[LightBlock]) -> Result<(), WitnessError> {
+        for light_block in light_blocks {
+            self.check_witnesses_for_block(light_block).await?;
+        }
+        Ok(())
+    }
+
+    /// Cross-checks the header of a light block fetched from the primary endpoints against every
+    /// witness. Returns the first divergence if a witness reports a different header at the same
+    /// height. Witnesses that can't be reached can't prove the primary wrong, but don't confirm the
+    /// header either, so fewer than the minimum number of matching witnesses is an error.
+    async fn check_witnesses_for_block(
+        &self,
+        light_block: &LightBlock,
+    ) -> Result<(), WitnessError> {
+        if self.witnesses.is_empty() {
+            return Ok(());
+        }
+
+        let height = light_block.signed_header.header.height.value();
+        let primary_hash = light_block.signed_header.header.hash();
+
+        let mut matches = 0;
+        for witness in &self.witnesses {
+            let witness_url = witness.current_url().to_string();
+            let witness_hash = match witness.get_commit(height).await {
+                Ok(commit) => commit.result.signed_header.header.hash(),
+                Err(e) => {
+                    warn!("Failed to cross-check block {} with witness: {}", height, e);
+                    continue;
+                }
+            };
+
+            if witness_hash != primary_hash {
+                return Err(Divergence {
+                    height,
+                    primary_hash,
+                    witness_url,
+                    witness_hash,
+                }
+                .into());
+            }
+            matches += 1;
+        }
+
+        if matches < self.min_witness_matches {
+            return Err(WitnessError::Unconfirmed {
+                height,
+                matches,
+                required: self.min_witness_matches,
+            });
+        }
+        info!(
+            "Block {} matches on {} of {} witnesses",
+            height,
+            matches,
+            self.witnesses.len()
+        );
+        Ok(())
+    }
+
+    /// The endpoint requests are currently sent to.
+    pub fn current_url(&self) -> &str {
+        &self.urls[self.current.load(Ordering::Relaxed)]
+    }
+
     /// Gets light blocks for the trusted and target block heights.
     pub async fn get_light_blocks(
         &self,
         trusted_block_height: u64,
         target_block_height: u64,
-    ) -> (LightBlock, LightBlock) {
-        let peer_id = self.get_peer_id().await.unwrap();
+    ) -> Result<(LightBlock, LightBlock), TendermintRpcError> {
+        let peer_id = self.get_peer_id().await?;
 
-        let trusted_light_block = self
-            .get_light_block(trusted_block_height, peer_id)
-            .await
-            .expect("Failed to generate light block 1");
-        let target_light_block = self
-            .get_light_block(target_block_height, peer_id)
-            .await
-            .expect("Failed to generate light block 2");
-        (trusted_light_block, target_light_block)
+        let trusted_light_block = self.get_light_block(trusted_block_height, peer_id).await?;
+        let target_light_block = self.get_light_block(target_block_height, peer_id).await?;
+        Ok((trusted_light_block, target_light_block))
     }
 
     /// Gets the latest block height from the Tendermint node.
-    pub async fn get_latest_block_height(&self) -> u64 {
-        let latest_commit = self.get_latest_commit().await.unwrap();
-        latest_commit.result.signed_header.header.height.value()
+    pub async fn get_latest_block_height(&self) -> Result<u64, TendermintRpcError> {
+        let latest_commit = self.get_latest_commit().await?;
+        Ok(latest_commit.result.signed_header.header.height.value())
     }
 
     /// Gets the block height from a given block hash.
-    pub async fn get_block_height_from_hash(&self, hash: &[u8]) -> u64 {
-        let block = self.get_block_by_hash(hash).await.unwrap();
-        block.result.block.header.height.value()
+    pub async fn get_block_height_from_hash(&self, hash: &[u8]) -> Result<u64, TendermintRpcError> {
+        let block = self.get_block_by_hash(hash).await?;
+        Ok(block.result.block.header.height.value())
     }
 
-    /// Gets a block by its hash.
-    async fn get_block_by_hash(&self, hash: &[u8]) -> Result<BlockResponse> {
-        let block_by_hash_url = format!(
-            "{}/block_by_hash?hash=0x{}",
-            self.url,
-            String::from_utf8(hex::encode(hash)).unwrap()
-        );
-        let response: BlockResponse = self
+    /// Sends a GET request for the given endpoint and decodes the JSON response. Failed requests
+    /// are retried with exponential backoff, then sent to the next RPC endpoint, until every
+    /// endpoint has been tried.
+    async fn get<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        query: &[(&str, String)],
+        height: Option<u64>,
+    ) -> Result<T, TendermintRpcError> {
+        let mut last_error = None;
+        for _ in 0..self.urls.len() {
+            let index = self.current.load(Ordering::Relaxed);
+            let url = format!("{}/{}", self.urls[index].trim_end_matches('/'), endpoint);
+
+            let mut backoff = self.retry_config.initial_backoff;
+            for attempt in 1..=self.retry_config.max_attempts {
+                match self.get_once(&url, query, height).await {
+                    Ok(response) => return Ok(response),
+                    Err(e) => {
+                        let retryable = e.is_retryable();
+                        warn!("Attempt {} for {} failed: {}", attempt, url, e);
+                        if let Some(error_counter) = &self.error_counter {
+                            error_counter.with_label_values(&[&self.urls[index]]).inc();
+                        }
+                        last_error = Some(e);
+                        // A missing height won't appear on retry, but may be on another endpoint.
+                        if !retryable || attempt == self.retry_config.max_attempts {
+                            break;
+                        }
+                        tokio::time::sleep(backoff).await;
+                        backoff = (backoff * self.retry_config.backoff_multiplier)
+                            .min(self.retry_config.max_backoff);
+                    }
+                }
+            }
+
+            // Rotate to the next endpoint, unless another request already did.
+            let next = (index + 1) % self.urls.len();
+            if self
+                .current
+                .compare_exchange(index, next, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+                && self.urls.len() > 1
+            {
+                warn!("Switching Tendermint RPC endpoint to {}", self.urls[next]);
+            }
+        }
+
+        Err(last_error.expect("at least one request was attempted"))
+    }
+
+    /// Sends a single GET request to the given URL and decodes the JSON response. If the request
+    /// is for a specific height, a JSON-RPC error about that height is reported as a missing
+    /// height.
+    async fn get_once<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        query: &[(&str, String)],
+        height: Option<u64>,
+    ) -> Result<T, TendermintRpcError> {
+        let http_error = |source| TendermintRpcError::Http {
+            url: url.to_string(),
+            source,
+        };
+
+        let body = self
             .client
-            .get(block_by_hash_url)
+            .get(url)
+            .query(query)
             .send()
-            .await?
-            .json::<BlockResponse>()
-            .await?;
-        Ok(response)
+            .await
+            .map_err(http_error)?
+            .text()
+            .await
+            .map_err(http_error)?;
+
+        // Tendermint nodes report failed requests as a JSON-RPC error object instead of a result.
+        if let Ok(response) = serde_json::from_str::<RpcErrorResponse>(&body) {
+            let url = url.to_string();
+            let reason = format!("{}: {}", response.error.message, response.error.data);
+            return Err(match height {
+                Some(height) if response.error.data.contains("height") => {
+                    TendermintRpcError::MissingHeight {
+                        url,
+                        height,
+                        reason,
+                    }
+                }
+                _ => TendermintRpcError::MalformedResponse { url, reason },
+            });
+        }
+
+        serde_json::from_str(&body).map_err(|source| TendermintRpcError::JsonDecode {
+            url: url.to_string(),
+            source,
+        })
+    }
+
+    /// Gets a block by its hash.
+    async fn get_block_by_hash(&self, hash: &[u8]) -> Result<BlockResponse, TendermintRpcError> {
+        let hash = String::from_utf8_lossy(&hex::encode(hash)).into_owned();
+        self.get("block_by_hash", &[("hash", format!("0x{}", hash))], None)
+            .await
     }
 
     /// Sorts the signatures in the signed header based on the descending order of validators' power.
@@ -104,91 +452,87 @@ impl TendermintRPCClient {
     }
 
     /// Gets the peer ID from the Tendermint node.
-    async fn get_peer_id(&self) -> Result<[u8; 20]> {
-        let client = Client::new();
-        let fetch_peer_id_url = format!("{}/status", self.url);
+    pub async fn get_peer_id(&self) -> Result<[u8; 20], TendermintRpcError> {
+        let response: PeerIdResponse = self.get("status", &[], None).await?;
+        let peer_id = response.result.node_info.id;
 
-        let response: PeerIdResponse = client
-            .get(fetch_peer_id_url)
-            .send()
-            .await?
-            .json::<PeerIdResponse>()
-            .await?;
-
-        Ok(hex::decode(response.result.node_info.id)
-            .unwrap()
-            .try_into()
-            .unwrap())
+        hex::decode(&peer_id)
+            .ok()
+            .and_then(|id| id.try_into().ok())
+            .ok_or(TendermintRpcError::InvalidPeerId(peer_id))
     }
 
     /// Gets a light block by its hash.
-    async fn get_light_block_by_hash(&self, hash: &[u8]) -> LightBlock {
-        let block = self.get_block_by_hash(hash).await.unwrap();
-        let peer_id = self.get_peer_id().await.unwrap();
-        self.get_light_block(
-            block.result.block.header.height.value(),
-            hex::decode(peer_id).unwrap().try_into().unwrap(),
-        )
-        .await
-        .unwrap()
+    async fn get_light_block_by_hash(&self, hash: &[u8]) -> Result<LightBlock, TendermintRpcError> {
+        let block = self.get_block_by_hash(hash).await?;
+        let peer_id = self.get_peer_id().await?;
+        self.get_light_block(block.result.block.header.height.value(), peer_id)
+            .await
     }
 
     /// Get the latest commit from the Tendermint node.
-    pub async fn get_latest_commit(&self) -> Result<CommitResponse> {
-        let url = format!("{}/commit", self.url);
-        let response: CommitResponse = self
-            .client
-            .get(url)
-            .send()
-            .await?
-            .json::<CommitResponse>()
-            .await?;
-        Ok(response)
+    pub async fn get_latest_commit(&self) -> Result<CommitResponse, TendermintRpcError> {
+        self.get("commit", &[], None).await
     }
 
     /// Get a commit for a specific block height.
-    pub async fn get_commit(&self, block_height: u64) -> Result<CommitResponse> {
-        let url = format!("{}/{}", self.url, "commit");
-
-        let response: CommitResponse = self
-            .client
-            .get(url)
-            .query(&[
-                ("height", block_height.to_string().as_str()),
-                ("per_page", "100"), // helpful only when fetching validators
-            ])
-            .send()
-            .await?
-            .json::<CommitResponse>()
-            .await?;
-        Ok(response)
+    pub async fn get_commit(
+        &self,
+        block_height: u64,
+    ) -> Result<CommitResponse, TendermintRpcError> {
+        self.get(
+            "commit",
+            &[
+                ("height", block_height.to_string()),
+                ("per_page", "100".to_string()), // helpful only when fetching validators
+            ],
+            Some(block_height),
+        )
+        .await
     }
 
     /// Get validators for a specific block height.
-    async fn get_validators(&self, block_height: u64) -> Result<Vec<Info>> {
-        let url = format!("{}/{}", self.url, "validators");
+    async fn get_validators(&self, block_height: u64) -> Result<Vec<Info>, TendermintRpcError> {
+        let malformed = |reason: String| TendermintRpcError::MalformedResponse {
+            url: format!("{}/validators", self.current_url()),
+            reason,
+        };
 
         let mut validators = vec![];
         let mut collected_validators = 0;
         let mut page_index = 1;
         loop {
-            let response = self
-                .client
-                .get(&url)
-                .query(&[
-                    ("height", block_height.to_string().as_str()),
-                    ("per_page", "100"),
-                    ("page", page_index.to_string().as_str()),
-                ])
-                .send()
-                .await?
-                .json::<ValidatorSetResponse>()
+            let response: ValidatorSetResponse = self
+                .get(
+                    "validators",
+                    &[
+                        ("height", block_height.to_string()),
+                        ("per_page", "100".to_string()),
+                        ("page", page_index.to_string()),
+                    ],
+                    Some(block_height),
+                )
                 .await?;
             let block_validator_set: BlockValidatorSet = response.result;
+            let count = block_validator_set
+                .count
+                .parse::<usize>()
+                .map_err(|_| malformed(format!("invalid count {}", block_validator_set.count)))?;
+            let total = block_validator_set
+                .total
+                .parse::<usize>()
+                .map_err(|_| malformed(format!("invalid total {}", block_validator_set.total)))?;
+            if count == 0 && collected_validators < total {
+                return Err(malformed(format!(
+                    "empty validators page {} at height {}",
+                    page_index, block_height
+                )));
+            }
+
             validators.extend(block_validator_set.validators);
-            collected_validators += block_validator_set.count.parse::<i32>().unwrap();
+            collected_validators += count;
 
-            if collected_validators >= block_validator_set.total.parse::<i32>().unwrap() {
+            if collected_validators >= total {
                 break;
             }
             page_index += 1;
@@ -198,7 +542,11 @@ impl TendermintRPCClient {
     }
 
     /// Gets a light block for a specific block height and peer ID.
-    async fn get_light_block(&self, block_height: u64, peer_id: [u8; 20]) -> Result<LightBlock> {
+    pub async fn get_light_block(
+        &self,
+        block_height: u64,
+        peer_id: [u8; 20],
+    ) -> Result<LightBlock, TendermintRpcError> {
         let commit_response = self.get_commit(block_height).await?;
         let mut signed_header = commit_response.result.signed_header;
 
@@ -0,0 +1,75 @@
+use std::time::Duration;
+use tendermint::Time;
+use tendermint_light_client_verifier::{
+    options::Options, types::LightBlock, ProdVerifier, Verdict, Verifier,
+};
+use thiserror::Error;
+
+/// Errors that prevent a light block update from being verified natively.
+#[derive(Debug, Error)]
+pub enum NativeVerificationError {
+    /// The next validator set of the trusted block does not hash to the header's
+    /// `next_validators_hash`.
+    #[error("next validators of trusted block {0} do not match its header")]
+    NextValidatorsMismatch(u64),
+    /// The verification time overflowed.
+    #[error("failed to compute the verification time for block {height}: {reason}")]
+    InvalidVerifyTime { height: u64, reason: String },
+}
+
+/// Verifies an update from the trusted light block to the target light block with the same
+/// `ProdVerifier` logic and verification time as the zkVM program, without generating a proof.
+///
+/// Returns `Verdict::Success` if the zkVM program would verify the update, otherwise the verdict
+/// the zkVM program would panic with.
+pub fn verify_natively(
+    trusted_light_block: &LightBlock,
+    target_light_block: &LightBlock,
+    options: &Options,
+) -> Result<Verdict, NativeVerificationError> {
+    let verify_time = verify_time(target_light_block)?;
+    verify_natively_at(
+        trusted_light_block,
+        target_light_block,
+        options,
+        verify_time,
+    )
+}
+
+/// Verifies an update like `verify_natively`, at the given verification time. Used to verify the
+/// hops of a path, which the zkVM program all verifies at the time of the last block in the path.
+pub fn verify_natively_at(
+    trusted_light_block: &LightBlock,
+    untrusted_light_block: &LightBlock,
+    options: &Options,
+    verify_time: Time,
+) -> Result<Verdict, NativeVerificationError> {
+    // Verify update header doesn't check this property.
+    if trusted_light_block.next_validators.hash()
+        != trusted_light_block.as_trusted_state().next_validators_hash
+    {
+        return Err(NativeVerificationError::NextValidatorsMismatch(
+            trusted_light_block.signed_header.header.height.value(),
+        ));
+    }
+
+    let vp = ProdVerifier::default();
+    Ok(vp.verify_update_header(
+        untrusted_light_block.as_untrusted_state(),
+        trusted_light_block.as_trusted_state(),
+        options,
+        verify_time,
+    ))
+}
+
+/// The time at which the zkVM program verifies an update to the target light block.
+pub(crate) fn verify_time(
+    target_light_block: &LightBlock,
+) -> Result<Time, NativeVerificationError> {
+    (target_light_block.time() + Duration::from_secs(20)).map_err(|e| {
+        NativeVerificationError::InvalidVerifyTime {
+            height: target_light_block.signed_header.header.height.value(),
+            reason: e.to_string(),
+        }
+    })
+}
@@ -0,0 +1,146 @@
+use log::{error, warn};
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+/// When the operator warns about and reacts to the contract's latest block approaching the end of
+/// the trusting period. Once the latest block is older than the trusting period, no update can be
+/// proven from it and the contract has to be redeployed.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExpiryWatchdogArgs {
+    /// Times left before the trusting period of the contract's latest block expires, in seconds,
+    /// at which to log a warning.
+    pub expiry_warning_thresholds: Vec<u64>,
+
+    /// Time left before the trusting period expires, in seconds, below which the operator proves
+    /// an update immediately, ignoring the minimum block step and the alignment of the target.
+    pub urgent_expiry_threshold: u64,
+}
+
+impl Default for ExpiryWatchdogArgs {
+    fn default() -> Self {
+        Self {
+            expiry_warning_thresholds: vec![7 * DAY_SECS, 3 * DAY_SECS, DAY_SECS],
+            urgent_expiry_threshold: 2 * DAY_SECS,
+        }
+    }
+}
+
+/// How close the contract's latest block is to the end of its trusting period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpiryStatus {
+    /// The time left is above every threshold.
+    Healthy,
+    /// The time left is below a warning threshold.
+    Warning { remaining: Duration },
+    /// The time left is below the urgent threshold.
+    Urgent { remaining: Duration },
+    /// The trusting period has expired.
+    Expired,
+}
+
+impl ExpiryStatus {
+    pub fn is_urgent(&self) -> bool {
+        matches!(self, ExpiryStatus::Urgent { .. })
+    }
+}
+
+/// Tracks the age of the contract's latest block and logs a warning each time it crosses a
+/// threshold.
+#[derive(Debug)]
+pub struct ExpiryWatchdog {
+    trusting_period: Duration,
+    warning_thresholds: Vec<Duration>,
+    urgent_threshold: Duration,
+    /// The trusted block and the lowest threshold last warned about for it.
+    last_warning: Option<(u64, Duration)>,
+    /// When the trusted block last checked crosses the urgent threshold, if it hadn't yet.
+    urgent_at: Option<Instant>,
+}
+
+impl ExpiryWatchdog {
+    pub fn new(args: &ExpiryWatchdogArgs, trusting_period: Duration) -> Self {
+        Self {
+            trusting_period,
+            warning_thresholds: args
+                .expiry_warning_thresholds
+                .iter()
+                .map(|secs| Duration::from_secs(*secs))
+                .collect(),
+            urgent_threshold: Duration::from_secs(args.urgent_expiry_threshold),
+            last_warning: None,
+            urgent_at: None,
+        }
+    }
+
+    /// The time until a trusted block of the given age crosses the urgent threshold, or `None` if
+    /// it already has.
+    pub fn time_until_urgent(&self, age: Duration) -> Option<Duration> {
+        self.trusting_period
+            .checked_sub(age)?
+            .checked_sub(self.urgent_threshold)
+            .filter(|until| !until.is_zero())
+    }
+
+    /// When the trusted block last checked crosses the urgent threshold, so the operator can update
+    /// it then rather than at its next poll. `None` if it already has.
+    pub fn urgent_at(&self) -> Option<Instant> {
+        self.urgent_at
+    }
+
+    /// Checks how close the trusted block is to the end of its trusting period, given its age, and
+    /// logs when a new threshold is crossed.
+    pub fn check(&mut self, trusted_block_height: u64, age: Duration) -> ExpiryStatus {
+        self.urgent_at = self
+            .time_until_urgent(age)
+            .map(|until| Instant::now() + until);
+
+        let Some(remaining) = self.trusting_period.checked_sub(age) else {
+            error!(
+                "The trusting period of block {} expired {}s ago. The contract can no longer be updated and must be redeployed.",
+                trusted_block_height,
+                (age - self.trusting_period).as_secs()
+            );
+            return ExpiryStatus::Expired;
+        };
+
+        let status = if remaining <= self.urgent_threshold {
+            ExpiryStatus::Urgent { remaining }
+        } else if self.warning_thresholds.iter().any(|t| remaining <= *t) {
+            ExpiryStatus::Warning { remaining }
+        } else {
+            return ExpiryStatus::Healthy;
+        };
+
+        // Only warn again once a lower threshold is crossed, or the trusted block changed.
+        let threshold = self
+            .warning_thresholds
+            .iter()
+            .chain([&self.urgent_threshold])
+            .filter(|t| remaining <= **t)
+            .min()
+            .copied()
+            .unwrap();
+        let already_warned = matches!(
+            self.last_warning,
+            Some((height, last)) if height == trusted_block_height && last <= threshold
+        );
+        if !already_warned {
+            self.last_warning = Some((trusted_block_height, threshold));
+            warn!(
+                "The trusting period of block {} expires in {}s{}",
+                trusted_block_height,
+                remaining.as_secs(),
+                if status.is_urgent() {
+                    ", proving an update immediately"
+                } else {
+                    ""
+                }
+            );
+        }
+
+        status
+    }
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use common::chain::{chain, header, rpc, sign, validators};
+use std::time::Duration;
+use tendermint_light_client_verifier::{
+    options::Options,
+    types::{LightBlock, TrustThreshold},
+};
+use tendermint_operator::{
+    bisection::plan_light_block_path,
+    util::{RetryConfig, TendermintRPCClient},
+};
+use tendermint_testgen::Validator;
+
+fn options() -> Options {
+    Options {
+        trust_threshold: TrustThreshold::ONE_THIRD,
+        trusting_period: Duration::from_secs(14 * 24 * 60 * 60),
+        clock_drift: Duration::ZERO,
+    }
+}
+
+fn client(url: String) -> TendermintRPCClient {
+    TendermintRPCClient::new(vec![url])
+        .unwrap()
+        .with_retry_config(RetryConfig {
+            max_attempts: 1,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(1),
+            backoff_multiplier: 2,
+        })
+}
+
+/// The heights of the light blocks of the path.
+fn heights(path: &[LightBlock]) -> Vec<u64> {
+    path.iter()
+        .map(|light_block| light_block.signed_header.header.height.value())
+        .collect()
+}
+
+/// The validators of a chain whose validator set is replaced at each of the given heights.
+fn rotating_validators(changes: &[u64]) -> impl Fn(u64) -> Vec<Validator> + '_ {
+    |height| {
+        let set = changes.iter().filter(|&&change| change <= height).count();
+        let ids: Vec<String> = (0..3).map(|i| format!("{}-{}", set, i)).collect();
+        validators(&ids.iter().map(String::as_str).collect::<Vec<_>>())
+    }
+}
+
+#[tokio::test]
+async fn test_plans_a_single_hop_when_the_target_verifies_directly() {
+    let server = rpc(chain(8, rotating_validators(&[]))).await;
+
+    let path = plan_light_block_path(&client(server.url()), 1, 8, &options())
+        .await
+        .unwrap();
+    assert_eq!(heights(&path), vec![1, 8]);
+}
+
+#[tokio::test]
+async fn test_bisects_at_the_pivot_after_a_validator_set_change() {
+    let server = rpc(chain(8, rotating_validators(&[5]))).await;
+
+    // The validators of block 1 didn't sign block 8, but those of block 4 did.
+    let path = plan_light_block_path(&client(server.url()), 1, 8, &options())
+        .await
+        .unwrap();
+    assert_eq!(heights(&path), vec![1, 4, 8]);
+}
+
+#[tokio::test]
+async fn test_bisects_recursively_across_several_validator_set_changes() {
+    let server = rpc(chain(8, rotating_validators(&[3, 6]))).await;
+
+    let path = plan_light_block_path(&client(server.url()), 1, 8, &options())
+        .await
+        .unwrap();
+    assert_eq!(heights(&path), vec![1, 2, 4, 5, 6, 8]);
+}
+
+#[tokio::test]
+async fn test_fails_to_plan_a_path_to_an_unverifiable_target() {
+    // Block 4 doesn't announce the validators of block 5, so no hop reaches the validators of
+    // block 8.
+    let validators_at = rotating_validators(&[5]);
+    let light_blocks = (1..=8)
+        .map(|height| {
+            let validators = validators_at(height);
+            sign(header(height, &validators, &validators))
+        })
+        .collect();
+    let server = rpc(light_blocks).await;
+
+    let err = plan_light_block_path(&client(server.url()), 1, 8, &options())
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("is invalid against block"),
+        "{}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn test_rejects_a_target_that_is_not_after_the_trusted_block() {
+    let server = rpc(chain(8, rotating_validators(&[]))).await;
+
+    let err = plan_light_block_path(&client(server.url()), 8, 8, &options())
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("is not after trusted block"),
+        "{}",
+        err
+    );
+    assert_eq!(server.requests(), 0);
+}
@@ -0,0 +1,143 @@
+use super::MockServer;
+use std::{collections::BTreeMap, time::Duration};
+use tendermint::Time;
+use tendermint_light_client_verifier::types::LightBlock;
+use tendermint_testgen::{
+    light_block::LightBlock as TestgenLightBlock, Commit, Generator, Header, Validator,
+};
+
+/// The chain ID of the generated light blocks.
+pub const CHAIN_ID: &str = "test-chain";
+
+/// Validators with the given IDs and equal voting power.
+pub fn validators(ids: &[&str]) -> Vec<Validator> {
+    ids.iter()
+        .map(|id| Validator::new(id).voting_power(50))
+        .collect()
+}
+
+/// The time of the generated block at the given height, a second after the previous block.
+pub fn block_time(height: u64) -> Time {
+    (Time::parse_from_rfc3339("2024-01-01T00:00:00Z").unwrap() + Duration::from_secs(height))
+        .unwrap()
+}
+
+/// The header of the block at the given height of the test chain.
+pub fn header(height: u64, validators: &[Validator], next_validators: &[Validator]) -> Header {
+    Header::new(validators)
+        .next_validators(next_validators)
+        .chain_id(CHAIN_ID)
+        .height(height)
+        .time(block_time(height))
+}
+
+/// A light block of the header, signed by all of its validators.
+pub fn sign(header: Header) -> LightBlock {
+    let validators = header.validators.clone().unwrap();
+    let next_validators = header.next_validators.clone().unwrap_or(validators.clone());
+    let commit = Commit::new(header.clone(), 1);
+    let light_block = TestgenLightBlock::new(header, commit)
+        .validators(&validators)
+        .next_validators(&next_validators)
+        .generate()
+        .unwrap();
+
+    LightBlock::new(
+        light_block.signed_header,
+        light_block.validators,
+        light_block.next_validators,
+        light_block.provider,
+    )
+}
+
+/// The light blocks at heights 1 to `length` of a chain with the given validators at each height.
+pub fn chain(length: u64, validators_at: impl Fn(u64) -> Vec<Validator>) -> Vec<LightBlock> {
+    (1..=length)
+        .map(|height| {
+            sign(header(
+                height,
+                &validators_at(height),
+                &validators_at(height + 1),
+            ))
+        })
+        .collect()
+}
+
+/// A Tendermint RPC endpoint serving the `/status`, `/commit` and `/validators` of the light
+/// blocks. Heights it has no light block at are reported as missing.
+pub async fn rpc(light_blocks: Vec<LightBlock>) -> MockServer {
+    let light_blocks: BTreeMap<u64, LightBlock> = light_blocks
+        .into_iter()
+        .map(|light_block| (light_block.signed_header.header.height.value(), light_block))
+        .collect();
+
+    MockServer::start(move |_, request| {
+        let path = request.split_whitespace().nth(1).unwrap_or_default();
+        if path.starts_with("/status") {
+            return (
+                200,
+                format!(
+                    r#"{{"result":{{"node_info":{{"id":"{}"}}}}}}"#,
+                    hex::encode([0u8; 20])
+                ),
+            );
+        }
+
+        let height = query_height(path);
+        if path.starts_with("/commit") {
+            if let Some(light_block) = height.and_then(|height| light_blocks.get(&height)) {
+                return (
+                    200,
+                    format!(
+                        r#"{{"result":{{"signed_header":{},"canonical":true}}}}"#,
+                        serde_json::to_string(&light_block.signed_header).unwrap()
+                    ),
+                );
+            }
+        } else if path.starts_with("/validators") {
+            // The validators after the last block are its next validators.
+            let validators = height.and_then(|height| {
+                light_blocks
+                    .get(&height)
+                    .map(|light_block| &light_block.validators)
+                    .or_else(|| {
+                        light_blocks
+                            .get(&height.saturating_sub(1))
+                            .map(|light_block| &light_block.next_validators)
+                    })
+            });
+            if let Some(validators) = validators {
+                let count = validators.validators().len();
+                return (
+                    200,
+                    format!(
+                        r#"{{"result":{{"block_height":"{}","validators":{},"count":"{}","total":"{}"}}}}"#,
+                        height.unwrap(),
+                        serde_json::to_string(validators.validators()).unwrap(),
+                        count,
+                        count
+                    ),
+                );
+            }
+        }
+
+        (
+            200,
+            format!(
+                r#"{{"error":{{"code":-32603,"message":"Internal error","data":"height {} is not available"}}}}"#,
+                height.unwrap_or_default()
+            ),
+        )
+    })
+    .await
+}
+
+/// The `height` query parameter of the request path.
+fn query_height(path: &str) -> Option<u64> {
+    let (_, query) = path.split_once('?')?;
+    query
+        .split('&')
+        .find_map(|param| param.strip_prefix("height="))?
+        .parse()
+        .ok()
+}
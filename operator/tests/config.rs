@@ -0,0 +1,226 @@
+use alloy_primitives::address;
+use clap::Parser;
+use std::{collections::HashMap, fs};
+use tendermint_light_client_verifier::types::TrustThreshold;
+use tendermint_operator::{
+    config::{ConfigOverrides, OperatorConfig},
+    relay::RelayTarget,
+    schedule::TargetPolicy,
+    ProofMode, ProverKind,
+};
+
+const CONFIG: &str = r#"
+proof_mode = "groth16"
+tendermint_rpc_urls = ["https://rpc.celestia-mocha.com/", "https://celestia-testnet-rpc.example/"]
+metrics_addr = "0.0.0.0:9090"
+
+[[relay_targets]]
+chain_id = 11155111
+contract = "0x5FbDB2315678afecb367f032d93F642f64180aa3"
+rpc_url = "https://ethereum-sepolia.publicnode.com/"
+
+[trust_options]
+trust_threshold = "1/3"
+trusting_period = 604800
+
+[schedule]
+poll_interval = 30
+target_policy = "aligned:100"
+max_block_step = 1000
+
+[fees]
+max_fee_per_gas = 100000000000
+"#;
+
+#[test]
+fn test_parses_config_file() {
+    let config = OperatorConfig::from_toml(CONFIG).unwrap();
+
+    assert_eq!(config.proof_mode, ProofMode::Groth16);
+    assert_eq!(config.tendermint_rpc_urls.len(), 2);
+    assert_eq!(config.metrics_addr, Some("0.0.0.0:9090".parse().unwrap()));
+    assert_eq!(
+        config.relay_targets,
+        vec![RelayTarget {
+            chain_id: 11155111,
+            contract: address!("5FbDB2315678afecb367f032d93F642f64180aa3"),
+            rpc_url: "https://ethereum-sepolia.publicnode.com/".to_string(),
+        }]
+    );
+    assert_eq!(
+        config.trust_options.trust_threshold,
+        TrustThreshold::ONE_THIRD
+    );
+    assert_eq!(config.trust_options.trusting_period, 604800);
+    assert_eq!(config.schedule.poll_interval, 30);
+    assert_eq!(config.schedule.target_policy, TargetPolicy::Aligned(100));
+    assert_eq!(config.schedule.max_block_step, Some(1000));
+    assert_eq!(config.fees.max_fee_per_gas, Some(100_000_000_000));
+    config.validate().unwrap();
+
+    // Settings missing from the file keep their defaults.
+    assert_eq!(config.trust_options.clock_drift, 0);
+    assert_eq!(config.schedule.min_block_step, 1);
+    assert_eq!(config.fees.fee_bump_percent, 12);
+    assert_eq!(config.state_file.to_str(), Some("operator_state.json"));
+    assert!(!config.report_cycles);
+    assert_eq!(config.prover, ProverKind::Cpu);
+}
+
+#[test]
+fn test_parses_example_config_file() {
+    let example = fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../operator.example.toml"
+    ))
+    .unwrap();
+    let config = OperatorConfig::from_toml(&example.replace(
+        "<SP1_TENDERMINT_ADDRESS>",
+        "0x5FbDB2315678afecb367f032d93F642f64180aa3",
+    ))
+    .unwrap();
+    config.validate().unwrap();
+}
+
+#[test]
+fn test_rejects_invalid_config() {
+    // Typos are rejected rather than silently falling back to the default.
+    assert!(OperatorConfig::from_toml("[schedule]\npoll_intervall = 30").is_err());
+    assert!(OperatorConfig::from_toml("[schedule]\ntarget_policy = \"aligned:0\"").is_err());
+    assert!(OperatorConfig::from_toml("[trust_options]\ntrust_threshold = \"2\"").is_err());
+
+    // Without RPC endpoints and contracts, there is nothing to relay.
+    assert!(OperatorConfig::default().validate().is_err());
+    let config = OperatorConfig::from_toml(&CONFIG.replace("groth16", "compressed")).unwrap();
+    assert!(config.validate().is_err());
+
+    // More witness matches than witnesses can never be reached.
+    let config = OperatorConfig::from_toml(&format!(
+        "tendermint_witness_rpc_urls = [\"https://witness.example/\"]\nmin_witness_matches = 2\n{}",
+        CONFIG
+    ))
+    .unwrap();
+    assert!(config.validate().is_err());
+}
+
+/// An environment with the given variables set.
+fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let vars = vars
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect::<HashMap<_, _>>();
+    move |name| vars.get(name).cloned()
+}
+
+#[test]
+fn test_env_overrides_config_file() {
+    let mut config = OperatorConfig::from_toml(CONFIG).unwrap();
+    config
+        .apply_env(env(&[
+            ("POLL_INTERVAL", "120"),
+            (
+                "RELAY_TARGETS",
+                "1:0x5FbDB2315678afecb367f032d93F642f64180aa3:http://localhost:8545,17000:0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512:http://localhost:8546",
+            ),
+            ("SP1_PROVER", "network"),
+            // Blank variables, e.g. from a `.env` file, don't override the file.
+            ("TARGET_POLICY", ""),
+        ]))
+        .unwrap();
+    assert_eq!(config.schedule.poll_interval, 120);
+    assert_eq!(config.schedule.target_policy, TargetPolicy::Aligned(100));
+    assert_eq!(config.prover, ProverKind::Network);
+    assert_eq!(
+        config
+            .relay_targets
+            .iter()
+            .map(|target| target.chain_id)
+            .collect::<Vec<_>>(),
+        vec![1, 17000]
+    );
+
+    // A single target can be set without RELAY_TARGETS.
+    let mut config = OperatorConfig::from_toml(CONFIG).unwrap();
+    config
+        .apply_env(env(&[
+            ("CHAIN_ID", "17000"),
+            (
+                "CONTRACT_ADDRESS",
+                "0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512",
+            ),
+            ("RPC_URL", "http://localhost:8546"),
+            // The prover of the old example environment.
+            ("SP1_PROVER", "local"),
+        ]))
+        .unwrap();
+    assert_eq!(
+        config.relay_targets,
+        vec![RelayTarget {
+            chain_id: 17000,
+            contract: address!("e7f1725E7734CE288F8367e1Bb143E90bb3F0512"),
+            rpc_url: "http://localhost:8546".to_string(),
+        }]
+    );
+    assert_eq!(config.prover, ProverKind::Cpu);
+
+    // The RPC URL alone, e.g. from the example `.env` file, doesn't replace the file's targets.
+    let mut config = OperatorConfig::from_toml(CONFIG).unwrap();
+    config
+        .apply_env(env(&[("RPC_URL", "http://localhost:8546")]))
+        .unwrap();
+    assert_eq!(config.relay_targets[0].chain_id, 11155111);
+
+    let mut config = OperatorConfig::default();
+    assert!(config.apply_env(env(&[("POLL_INTERVAL", "soon")])).is_err());
+    assert!(config.apply_env(env(&[("SP1_PROVER", "gpu")])).is_err());
+    assert!(config.apply_env(env(&[("CHAIN_ID", "1")])).is_err());
+}
+
+#[derive(Parser)]
+struct Cli {
+    #[clap(flatten)]
+    overrides: ConfigOverrides,
+}
+
+#[test]
+fn test_flags_override_env_and_config_file() {
+    let mut config = OperatorConfig::from_toml(CONFIG).unwrap();
+    config
+        .apply_env(env(&[("POLL_INTERVAL", "120"), ("PROOF_MODE", "plonk")]))
+        .unwrap();
+    let cli = Cli::try_parse_from([
+        "operator",
+        "--poll-interval",
+        "10",
+        "--trust-threshold",
+        "2/3",
+        "--trusting-period",
+        "86400",
+        "--min-block-step",
+        "5",
+        "--relay-targets",
+        "17000:0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512:http://localhost:8546",
+    ])
+    .unwrap();
+    config.apply_overrides(cli.overrides);
+
+    assert_eq!(config.schedule.poll_interval, 10);
+    assert_eq!(
+        config.trust_options.trust_threshold,
+        TrustThreshold::TWO_THIRDS
+    );
+    assert_eq!(config.trust_options.trusting_period, 86400);
+    assert_eq!(config.schedule.min_block_step, 5);
+    assert_eq!(config.relay_targets[0].chain_id, 17000);
+    // Settings without a flag keep the value of the environment or the file.
+    assert_eq!(config.proof_mode, ProofMode::Plonk);
+    assert_eq!(config.schedule.target_policy, TargetPolicy::Aligned(100));
+    assert_eq!(config.schedule.max_block_step, Some(1000));
+
+    let cli = Cli::try_parse_from(["operator", "--proof-mode", "groth16"]).unwrap();
+    config.apply_overrides(cli.overrides);
+    assert_eq!(config.proof_mode, ProofMode::Groth16);
+
+    assert!(Cli::try_parse_from(["operator", "--target-policy", "aligned:0"]).is_err());
+    assert!(Cli::try_parse_from(["operator", "--trust-threshold", "2"]).is_err());
+}
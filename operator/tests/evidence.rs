@@ -0,0 +1,160 @@
+mod common;
+
+use common::chain::{chain, header, rpc, sign, validators};
+use std::time::Duration;
+use tendermint::AppHash;
+use tendermint_light_client_verifier::{
+    options::Options,
+    types::{LightBlock, TrustThreshold},
+};
+use tendermint_operator::{
+    evidence::LightClientAttackEvidence,
+    util::{Divergence, RetryConfig, TendermintRPCClient},
+};
+use tendermint_testgen::Validator;
+
+fn options() -> Options {
+    Options {
+        trust_threshold: TrustThreshold::ONE_THIRD,
+        trusting_period: Duration::from_secs(14 * 24 * 60 * 60),
+        clock_drift: Duration::ZERO,
+    }
+}
+
+/// The validators of a chain whose validator set is replaced at block 5.
+fn validators_at(height: u64) -> Vec<Validator> {
+    if height < 5 {
+        validators(&["a", "b", "c"])
+    } else {
+        validators(&["d", "e", "f"])
+    }
+}
+
+/// A block at the given height, signed by the same validators as the block of the chain at that
+/// height, that conflicts with it.
+fn conflicting_block(height: u64) -> LightBlock {
+    sign(
+        header(height, &validators_at(height), &validators_at(height + 1))
+            .app_hash(AppHash::try_from(vec![1; 32]).unwrap()),
+    )
+}
+
+#[test]
+fn test_verifies_conflicting_blocks_signed_by_the_common_validators() {
+    let chain = chain(8, validators_at);
+    let evidence = LightClientAttackEvidence {
+        common_path: vec![chain[0].clone()],
+        primary_light_block: chain[3].clone(),
+        witness_light_block: conflicting_block(4),
+    };
+
+    evidence.verify(&options()).unwrap();
+    assert_eq!(evidence.conflict_height(), 4);
+}
+
+#[test]
+fn test_rejects_blocks_that_do_not_conflict() {
+    let chain = chain(8, validators_at);
+    let evidence = LightClientAttackEvidence {
+        common_path: vec![chain[0].clone()],
+        primary_light_block: chain[3].clone(),
+        witness_light_block: chain[3].clone(),
+    };
+
+    let err = evidence.verify(&options()).unwrap_err();
+    assert!(err.to_string().contains("do not conflict"), "{}", err);
+}
+
+#[test]
+fn test_rejects_a_conflicting_block_of_other_validators() {
+    // A faulty witness serving a block signed by unknown validators is not an attack.
+    let chain = chain(8, validators_at);
+    let evidence = LightClientAttackEvidence {
+        common_path: vec![chain[0].clone()],
+        primary_light_block: chain[3].clone(),
+        witness_light_block: sign(header(
+            4,
+            &validators(&["x", "y", "z"]),
+            &validators(&["x", "y", "z"]),
+        )),
+    };
+
+    let err = evidence.verify(&options()).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("The witness block at height 4 does not verify"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_verifies_conflicting_blocks_against_a_later_common_block() {
+    // The validators of block 1 didn't sign block 8, but those of block 4 did.
+    let chain = chain(8, validators_at);
+    let evidence = LightClientAttackEvidence {
+        common_path: vec![chain[0].clone()],
+        primary_light_block: chain[7].clone(),
+        witness_light_block: conflicting_block(8),
+    };
+    assert!(evidence.verify(&options()).is_err());
+
+    let evidence = LightClientAttackEvidence {
+        common_path: vec![chain[0].clone(), chain[3].clone()],
+        ..evidence
+    };
+    evidence.verify(&options()).unwrap();
+    let common_header = &evidence.common_light_block().signed_header.header;
+    assert_eq!(common_header.height.value(), 4);
+
+    // The common block must verify along the path from the trusted block.
+    let evidence = LightClientAttackEvidence {
+        common_path: vec![chain[0].clone(), chain[6].clone()],
+        ..evidence
+    };
+    let err = evidence.verify(&options()).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Block 7 on the path to the common block does not verify"),
+        "{}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn test_collects_the_conflicting_blocks_from_the_primary_and_the_witness() {
+    let chain = chain(8, validators_at);
+    let mut forked_chain = chain.clone();
+    forked_chain[3] = conflicting_block(4);
+    let primary = rpc(chain.clone()).await;
+    let witness = rpc(forked_chain).await;
+    let client = TendermintRPCClient::new(vec![primary.url()])
+        .unwrap()
+        .with_witnesses(vec![witness.url()])
+        .with_retry_config(RetryConfig {
+            max_attempts: 1,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(1),
+            backoff_multiplier: 2,
+        });
+
+    let divergence = Divergence {
+        height: 4,
+        primary_hash: chain[3].signed_header.header.hash(),
+        witness_url: witness.url(),
+        witness_hash: conflicting_block(4).signed_header.header.hash(),
+    };
+    let evidence = LightClientAttackEvidence::collect(&client, vec![chain[0].clone()], &divergence)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        evidence.primary_light_block.signed_header.header.hash(),
+        divergence.primary_hash
+    );
+    assert_eq!(
+        evidence.witness_light_block.signed_header.header.hash(),
+        divergence.witness_hash
+    );
+    evidence.verify(&options()).unwrap();
+}
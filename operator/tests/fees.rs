@@ -0,0 +1,69 @@
+use tendermint_operator::fees::{FeeArgs, Fees};
+
+fn fees(max_fee_per_gas: u128, max_priority_fee_per_gas: u128) -> Fees {
+    Fees {
+        max_fee_per_gas,
+        max_priority_fee_per_gas,
+    }
+}
+
+fn fee_args(max_fee_per_gas: Option<u128>, max_priority_fee_per_gas: Option<u128>) -> FeeArgs {
+    FeeArgs {
+        max_fee_per_gas,
+        max_priority_fee_per_gas,
+        ..FeeArgs::default()
+    }
+}
+
+#[test]
+fn test_caps_estimated_fees() {
+    assert_eq!(fee_args(None, None).cap(fees(100, 10)), fees(100, 10));
+    assert_eq!(fee_args(Some(50), Some(5)).cap(fees(100, 10)), fees(50, 5));
+    assert_eq!(
+        fee_args(Some(200), Some(20)).cap(fees(100, 10)),
+        fees(100, 10)
+    );
+    // The priority fee never exceeds the max fee.
+    assert_eq!(fee_args(Some(8), None).cap(fees(100, 10)), fees(8, 8));
+}
+
+#[test]
+fn test_bumps_fees_of_replacements() {
+    let args = FeeArgs {
+        fee_bump_percent: 12,
+        ..fee_args(None, None)
+    };
+    assert_eq!(args.bump(fees(100, 10)), Some(fees(112, 12)));
+    // Bumps are rounded up.
+    assert_eq!(args.bump(fees(101, 1)), Some(fees(114, 2)));
+
+    // A bump the caps leave at least 10% room for is capped.
+    let args = FeeArgs {
+        fee_bump_percent: 50,
+        ..fee_args(Some(115), Some(11))
+    };
+    assert_eq!(args.bump(fees(100, 10)), Some(fees(115, 11)));
+
+    // Nodes reject replacements bumped by less than 10%.
+    assert_eq!(fee_args(Some(105), None).bump(fees(100, 10)), None);
+    assert_eq!(fee_args(None, Some(10)).bump(fees(100, 10)), None);
+}
+
+#[test]
+fn test_validate_fee_args() {
+    assert!(FeeArgs::default().validate().is_ok());
+    assert!(fee_args(Some(10), Some(10)).validate().is_ok());
+    assert!(fee_args(Some(10), Some(11)).validate().is_err());
+    assert!(FeeArgs {
+        fee_bump_percent: 9,
+        ..FeeArgs::default()
+    }
+    .validate()
+    .is_err());
+    assert!(FeeArgs {
+        replacement_blocks: 0,
+        ..FeeArgs::default()
+    }
+    .validate()
+    .is_err());
+}
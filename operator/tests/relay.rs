@@ -0,0 +1,54 @@
+use alloy_primitives::{address, B256};
+use tendermint_operator::relay::{group_by_trusted_state, RelayTarget, TrustedState};
+
+fn trusted_state(height: u64, header: u8) -> TrustedState {
+    TrustedState {
+        height,
+        header: B256::repeat_byte(header),
+    }
+}
+
+#[test]
+fn test_parse_relay_target() {
+    assert_eq!(
+        "11155111:0x5FbDB2315678afecb367f032d93F642f64180aa3:http://localhost:8545"
+            .parse::<RelayTarget>()
+            .unwrap(),
+        RelayTarget {
+            chain_id: 11155111,
+            contract: address!("5FbDB2315678afecb367f032d93F642f64180aa3"),
+            rpc_url: "http://localhost:8545".to_string(),
+        }
+    );
+    assert!("11155111:0x5FbDB2315678afecb367f032d93F642f64180aa3"
+        .parse::<RelayTarget>()
+        .is_err());
+    assert!(
+        "sepolia:0x5FbDB2315678afecb367f032d93F642f64180aa3:http://localhost:8545"
+            .parse::<RelayTarget>()
+            .is_err()
+    );
+    assert!("11155111:0x5FbDB:http://localhost:8545"
+        .parse::<RelayTarget>()
+        .is_err());
+}
+
+#[test]
+fn test_groups_contracts_by_trusted_state() {
+    let groups = group_by_trusted_state([
+        ("a", trusted_state(200, 2)),
+        ("b", trusted_state(100, 1)),
+        ("c", trusted_state(200, 2)),
+        // The same height with a different header needs a different proof.
+        ("d", trusted_state(200, 3)),
+    ]);
+
+    assert_eq!(
+        groups.into_iter().collect::<Vec<_>>(),
+        vec![
+            (trusted_state(100, 1), vec!["b"]),
+            (trusted_state(200, 2), vec!["a", "c"]),
+            (trusted_state(200, 3), vec!["d"]),
+        ]
+    );
+}
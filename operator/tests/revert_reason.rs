@@ -0,0 +1,57 @@
+use alloy_primitives::{keccak256, FixedBytes};
+use tendermint_operator::contract::RevertReason;
+
+fn selector(signature: &str) -> Vec<u8> {
+    keccak256(signature)[..4].to_vec()
+}
+
+#[test]
+fn test_decodes_contract_errors() {
+    assert_eq!(
+        RevertReason::decode(&selector("InvalidTrustedHeader()")),
+        RevertReason::InvalidTrustedHeader
+    );
+    assert_eq!(
+        RevertReason::decode(&selector("InvalidTrustOptions()")),
+        RevertReason::InvalidTrustOptions
+    );
+    assert_eq!(
+        RevertReason::decode(&selector("InvalidChainId()")),
+        RevertReason::InvalidChainId
+    );
+    assert_eq!(
+        RevertReason::decode(&selector("ClientFrozen()")),
+        RevertReason::ClientFrozen
+    );
+}
+
+#[test]
+fn test_decodes_verifier_gateway_errors() {
+    let received = FixedBytes::<4>::from([1, 2, 3, 4]);
+    let expected = FixedBytes::<4>::from([5, 6, 7, 8]);
+    // Each bytes4 argument is left-aligned in a 32 byte word.
+    let mut data = selector("WrongVerifierSelector(bytes4,bytes4)");
+    let mut word = [0u8; 32];
+    word[..4].copy_from_slice(received.as_slice());
+    data.extend_from_slice(&word);
+    word[..4].copy_from_slice(expected.as_slice());
+    data.extend_from_slice(&word);
+
+    assert_eq!(
+        RevertReason::decode(&data),
+        RevertReason::WrongVerifierSelector { received, expected }
+    );
+    assert_eq!(
+        RevertReason::decode(&selector("InvalidProof()")),
+        RevertReason::InvalidProof
+    );
+}
+
+#[test]
+fn test_keeps_unknown_revert_data() {
+    let data = selector("Unknown()");
+    assert_eq!(
+        RevertReason::decode(&data),
+        RevertReason::Unknown(data.clone().into())
+    );
+}
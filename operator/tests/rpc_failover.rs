@@ -0,0 +1,112 @@
+mod common;
+
+use common::MockServer;
+use std::time::Duration;
+use tendermint_operator::util::{RetryConfig, TendermintRPCClient, TendermintRpcError};
+
+const PEER_ID: &str = "0123456789abcdef0123456789abcdef01234567";
+
+fn status_response() -> (u16, String) {
+    (
+        200,
+        format!(r#"{{"result":{{"node_info":{{"id":"{}"}}}}}}"#, PEER_ID),
+    )
+}
+
+fn missing_height_response() -> (u16, String) {
+    (
+        500,
+        r#"{"jsonrpc":"2.0","id":-1,"error":{"code":-32603,"message":"Internal error","data":"height 1 is not available, lowest height is 100"}}"#
+            .to_string(),
+    )
+}
+
+fn retry_config() -> RetryConfig {
+    RetryConfig {
+        max_attempts: 3,
+        initial_backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_millis(5),
+        backoff_multiplier: 2,
+    }
+}
+
+#[tokio::test]
+async fn test_retries_transient_failures_on_the_same_endpoint() {
+    let server = MockServer::start(|index, _| {
+        if index < 2 {
+            (503, "rate limited".to_string())
+        } else {
+            status_response()
+        }
+    })
+    .await;
+
+    let client = TendermintRPCClient::new(vec![server.url()])
+        .unwrap()
+        .with_retry_config(retry_config());
+    let peer_id = client.get_peer_id().await.unwrap();
+
+    assert_eq!(hex::encode(peer_id), PEER_ID);
+    assert_eq!(server.requests(), 3);
+}
+
+#[tokio::test]
+async fn test_fails_over_to_the_next_endpoint() {
+    let failing = MockServer::start(|_, _| (502, "bad gateway".to_string())).await;
+    let healthy = MockServer::start(|_, _| status_response()).await;
+
+    let client = TendermintRPCClient::new(vec![failing.url(), healthy.url()])
+        .unwrap()
+        .with_retry_config(retry_config());
+    let peer_id = client.get_peer_id().await.unwrap();
+
+    assert_eq!(hex::encode(peer_id), PEER_ID);
+    assert_eq!(failing.requests(), 3);
+    assert_eq!(healthy.requests(), 1);
+
+    // Later requests stay on the healthy endpoint.
+    assert_eq!(client.current_url(), healthy.url());
+    client.get_peer_id().await.unwrap();
+    assert_eq!(failing.requests(), 3);
+    assert_eq!(healthy.requests(), 2);
+}
+
+#[tokio::test]
+async fn test_missing_height_rotates_without_retrying() {
+    let pruned = MockServer::start(|_, _| missing_height_response()).await;
+    let archive = MockServer::start(|_, _| missing_height_response()).await;
+
+    let client = TendermintRPCClient::new(vec![pruned.url(), archive.url()])
+        .unwrap()
+        .with_retry_config(retry_config());
+    let err = client.get_commit(1).await.unwrap_err();
+
+    assert!(matches!(
+        err,
+        TendermintRpcError::MissingHeight { height: 1, .. }
+    ));
+    assert_eq!(pruned.requests(), 1);
+    assert_eq!(archive.requests(), 1);
+}
+
+#[tokio::test]
+async fn test_returns_the_last_error_when_every_endpoint_fails() {
+    let first = MockServer::start(|_, _| (500, "not json".to_string())).await;
+    let second = MockServer::start(|_, _| (200, r#"{"result":{}}"#.to_string())).await;
+
+    let client = TendermintRPCClient::new(vec![first.url(), second.url()])
+        .unwrap()
+        .with_retry_config(retry_config());
+    let err = client.get_peer_id().await.unwrap_err();
+
+    assert!(
+        matches!(err, TendermintRpcError::JsonDecode { ref url, .. } if url.starts_with(&second.url()))
+    );
+    assert_eq!(first.requests(), 3);
+    assert_eq!(second.requests(), 3);
+}
+
+#[test]
+fn test_requires_at_least_one_endpoint() {
+    assert!(TendermintRPCClient::new(vec![]).is_err());
+}
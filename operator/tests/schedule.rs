@@ -0,0 +1,93 @@
+use tendermint_operator::schedule::{ScheduleArgs, TargetPolicy};
+
+fn schedule(
+    target_policy: TargetPolicy,
+    min_block_step: u64,
+    max_block_step: Option<u64>,
+) -> ScheduleArgs {
+    ScheduleArgs {
+        poll_interval: 60,
+        max_block_step,
+        min_block_step,
+        target_policy,
+    }
+}
+
+#[test]
+fn test_parse_target_policy() {
+    assert_eq!(
+        "latest".parse::<TargetPolicy>().unwrap(),
+        TargetPolicy::Latest
+    );
+    assert_eq!(
+        "confirmations:5".parse::<TargetPolicy>().unwrap(),
+        TargetPolicy::Confirmations(5)
+    );
+    assert_eq!(
+        "aligned:100".parse::<TargetPolicy>().unwrap(),
+        TargetPolicy::Aligned(100)
+    );
+    assert!("aligned:0".parse::<TargetPolicy>().is_err());
+    assert!("aligned".parse::<TargetPolicy>().is_err());
+    assert!("latest:1".parse::<TargetPolicy>().is_err());
+}
+
+#[test]
+fn test_select_target_by_policy() {
+    assert_eq!(
+        schedule(TargetPolicy::Latest, 1, None).select_target(100, 250),
+        Some(250)
+    );
+    assert_eq!(
+        schedule(TargetPolicy::Confirmations(10), 1, None).select_target(100, 250),
+        Some(240)
+    );
+    assert_eq!(
+        schedule(TargetPolicy::Aligned(100), 1, None).select_target(100, 250),
+        Some(200)
+    );
+}
+
+#[test]
+fn test_select_target_respects_steps() {
+    // Below the minimum step.
+    assert_eq!(
+        schedule(TargetPolicy::Latest, 50, None).select_target(100, 149),
+        None
+    );
+    // No aligned block after the trusted block yet.
+    assert_eq!(
+        schedule(TargetPolicy::Aligned(100), 1, None).select_target(100, 199),
+        None
+    );
+    // Capped at the maximum step, staying aligned.
+    assert_eq!(
+        schedule(TargetPolicy::Latest, 1, Some(1000)).select_target(100, 5000),
+        Some(1100)
+    );
+    assert_eq!(
+        schedule(TargetPolicy::Aligned(300), 1, Some(1000)).select_target(100, 5000),
+        Some(900)
+    );
+}
+
+#[test]
+fn test_validate_schedule() {
+    assert!(schedule(TargetPolicy::Latest, 1, None).validate().is_ok());
+    assert!(schedule(TargetPolicy::Latest, 0, None).validate().is_err());
+    assert!(schedule(TargetPolicy::Latest, 10, Some(5))
+        .validate()
+        .is_err());
+    assert!(schedule(TargetPolicy::Aligned(100), 1, Some(50))
+        .validate()
+        .is_err());
+}
+
+#[test]
+fn test_select_urgent_target_ignores_min_step_and_alignment() {
+    let schedule = schedule(TargetPolicy::Aligned(100), 50, Some(1000));
+    assert_eq!(schedule.select_target(100, 120), None);
+    assert_eq!(schedule.select_urgent_target(100, 120), Some(120));
+    assert_eq!(schedule.select_urgent_target(100, 5000), Some(1100));
+    assert_eq!(schedule.select_urgent_target(100, 100), None);
+}
@@ -0,0 +1,203 @@
+mod common;
+
+use alloy::{
+    consensus::{SignableTransaction, TxEip1559},
+    network::{NetworkWallet, TxSigner},
+    signers::{local::PrivateKeySigner, SignerSync},
+};
+use alloy_primitives::{Address, B256};
+use common::MockServer;
+use std::{collections::HashMap, fs, path::PathBuf};
+use tendermint_operator::signer::{KeystorePassword, RemoteSigner, SignerConfig, WatchOnlySigner};
+
+const PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+const OTHER_PRIVATE_KEY: &str =
+    "0x6c1fb05e3c0f8b0d0e6e2a3b4f7a9c8d1e2f3a4b5c6d7e8f9a0b1c2d3e4f5a6b";
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("operator-signer-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Writes a keystore of `PRIVATE_KEY` encrypted with the password, and a file with the password
+/// followed by a newline.
+fn write_keystore(name: &str, password: &str) -> (PathBuf, PathBuf) {
+    let dir = temp_dir(name);
+    let private_key = PRIVATE_KEY.parse::<PrivateKeySigner>().unwrap();
+    PrivateKeySigner::encrypt_keystore(
+        &dir,
+        &mut rand::thread_rng(),
+        private_key.to_bytes(),
+        password,
+        Some("keystore.json"),
+    )
+    .unwrap();
+    let password_file = dir.join("password");
+    fs::write(&password_file, format!("{}\n", password)).unwrap();
+    (dir.join("keystore.json"), password_file)
+}
+
+/// A signing service that signs every requested hash with the given key.
+async fn signing_service(private_key: &'static str) -> MockServer {
+    MockServer::start(move |_, request| {
+        let body = request.split("\r\n\r\n").nth(1).unwrap_or_default();
+        let body: serde_json::Value = serde_json::from_str(body).unwrap();
+        let hash = body["hash"].as_str().unwrap().parse::<B256>().unwrap();
+        let signer = private_key.parse::<PrivateKeySigner>().unwrap();
+        let signature = signer.sign_hash_sync(&hash).unwrap();
+        (
+            200,
+            format!(
+                r#"{{"signature":"0x{}"}}"#,
+                hex::encode(signature.as_bytes())
+            ),
+        )
+    })
+    .await
+}
+
+fn address(private_key: &str) -> Address {
+    private_key.parse::<PrivateKeySigner>().unwrap().address()
+}
+
+/// An environment with the given variables set.
+fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let vars = vars
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect::<HashMap<_, _>>();
+    move |name| vars.get(name).cloned()
+}
+
+#[test]
+fn test_reads_the_signer_from_the_environment_ignoring_blank_variables() {
+    let signer = SignerConfig::from_env(env(&[
+        ("KEYSTORE_PATH", "keystore.json"),
+        ("KEYSTORE_PASSWORD_FILE", ""),
+        ("PRIVATE_KEY", PRIVATE_KEY),
+    ]))
+    .unwrap();
+    assert_eq!(
+        signer,
+        SignerConfig::Keystore {
+            path: "keystore.json".into(),
+            password: KeystorePassword::Prompt,
+        }
+    );
+
+    // A blank keystore path, as left in a `.env` file, doesn't select a keystore.
+    let signer = SignerConfig::from_env(env(&[
+        ("KEYSTORE_PATH", " "),
+        ("REMOTE_SIGNER_URL", ""),
+        ("PRIVATE_KEY", PRIVATE_KEY),
+    ]))
+    .unwrap();
+    assert_eq!(signer, SignerConfig::PrivateKey(PRIVATE_KEY.to_string()));
+
+    assert!(SignerConfig::from_env(env(&[("PRIVATE_KEY", "")])).is_err());
+}
+
+#[test]
+fn test_decrypts_keystore_with_password_file() {
+    let (path, password_file) = write_keystore("decrypt", "correct horse");
+    let signer = SignerConfig::Keystore {
+        path,
+        password: KeystorePassword::File(password_file),
+    };
+
+    let wallet = signer.wallet().unwrap();
+    assert_eq!(
+        NetworkWallet::<alloy::network::Ethereum>::default_signer_address(&wallet),
+        address(PRIVATE_KEY)
+    );
+}
+
+#[test]
+fn test_rejects_wrong_keystore_password() {
+    let (path, _) = write_keystore("wrong-password", "correct horse");
+    let password_file = path.with_file_name("wrong-password");
+    fs::write(&password_file, "battery staple\n").unwrap();
+    let signer = SignerConfig::Keystore {
+        path,
+        password: KeystorePassword::File(password_file),
+    };
+
+    assert!(signer.wallet().is_err());
+}
+
+#[test]
+fn test_reads_the_address_without_decrypting_the_keystore() {
+    let (path, _) = write_keystore("address", "correct horse");
+    // The password file doesn't exist, so the keystore can't be decrypted.
+    let signer = SignerConfig::Keystore {
+        path: path.clone(),
+        password: KeystorePassword::File(path.with_file_name("missing-password")),
+    };
+    let err = signer.address().unwrap_err();
+    assert!(
+        err.to_string().contains("does not contain its address"),
+        "{}",
+        err
+    );
+
+    let mut keystore: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    keystore["address"] = hex::encode(address(PRIVATE_KEY)).into();
+    fs::write(&path, keystore.to_string()).unwrap();
+    assert_eq!(signer.address().unwrap(), address(PRIVATE_KEY));
+
+    let signer = SignerConfig::PrivateKey(PRIVATE_KEY.to_string());
+    assert_eq!(signer.address().unwrap(), address(PRIVATE_KEY));
+}
+
+#[tokio::test]
+async fn test_watch_only_signer_refuses_to_sign() {
+    let signer = WatchOnlySigner::new(address(PRIVATE_KEY));
+    assert_eq!(signer.address(), address(PRIVATE_KEY));
+
+    let mut tx = TxEip1559::default();
+    assert!(signer.sign_transaction(&mut tx).await.is_err());
+}
+
+#[tokio::test]
+async fn test_remote_signer_signs_transactions() {
+    let server = signing_service(PRIVATE_KEY).await;
+    let signer = RemoteSigner::new(&server.url(), address(PRIVATE_KEY));
+
+    let mut tx = TxEip1559 {
+        chain_id: 1,
+        nonce: 7,
+        gas_limit: 100_000,
+        max_fee_per_gas: 20_000_000_000,
+        max_priority_fee_per_gas: 1_000_000_000,
+        ..Default::default()
+    };
+    let signature = signer.sign_transaction(&mut tx).await.unwrap();
+
+    assert_eq!(
+        signature
+            .recover_address_from_prehash(&tx.signature_hash())
+            .unwrap(),
+        address(PRIVATE_KEY)
+    );
+    assert_eq!(server.requests(), 1);
+}
+
+#[tokio::test]
+async fn test_remote_signer_rejects_signatures_by_another_key() {
+    let server = signing_service(OTHER_PRIVATE_KEY).await;
+    let signer = RemoteSigner::new(&server.url(), address(PRIVATE_KEY));
+
+    let err = signer.sign_hash(&B256::repeat_byte(1)).await.unwrap_err();
+    assert!(err.to_string().contains("instead of"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_remote_signer_surfaces_service_errors() {
+    let server = MockServer::start(|_, _| (403, "address not allowed".to_string())).await;
+    let signer = RemoteSigner::new(&server.url(), address(PRIVATE_KEY));
+
+    let err = signer.sign_hash(&B256::repeat_byte(1)).await.unwrap_err();
+    assert!(err.to_string().contains("address not allowed"), "{}", err);
+}
@@ -0,0 +1,28 @@
+use alloy_primitives::B256;
+use std::{env, fs};
+use tendermint_operator::state::StateStore;
+
+#[test]
+fn test_persists_the_proof_request_of_the_in_flight_job() {
+    let path = env::temp_dir().join(format!("operator-state-{}.json", std::process::id()));
+    let _ = fs::remove_file(&path);
+
+    let mut store = StateStore::open(&path).unwrap();
+    store.start_job(10, 20).unwrap();
+    store.record_request(B256::repeat_byte(1)).unwrap();
+
+    // A restart waits for the same request.
+    let mut store = StateStore::open(&path).unwrap();
+    let job = store.state().in_flight_job.clone().unwrap();
+    assert_eq!((job.trusted_height, job.target_height), (10, 20));
+    assert_eq!(job.request_id, Some(B256::repeat_byte(1)));
+
+    store.clear_request().unwrap();
+    let store = StateStore::open(&path).unwrap();
+    assert_eq!(
+        store.state().in_flight_job.clone().unwrap().request_id,
+        None
+    );
+
+    fs::remove_file(&path).unwrap();
+}
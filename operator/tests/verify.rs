@@ -0,0 +1,112 @@
+mod common;
+
+use common::chain::{block_time, header, sign, validators};
+use std::time::Duration;
+use tendermint_light_client_verifier::{
+    errors::VerificationErrorDetail,
+    options::Options,
+    types::{LightBlock, TrustThreshold},
+    Verdict,
+};
+use tendermint_operator::verify::{verify_natively, verify_natively_at, NativeVerificationError};
+
+fn options() -> Options {
+    Options {
+        trust_threshold: TrustThreshold::ONE_THIRD,
+        trusting_period: Duration::from_secs(14 * 24 * 60 * 60),
+        clock_drift: Duration::ZERO,
+    }
+}
+
+/// A light block at the given height, signed and followed by the validators with the given IDs.
+fn light_block(height: u64, ids: &[&str]) -> LightBlock {
+    let validators = validators(ids);
+    sign(header(height, &validators, &validators))
+}
+
+#[test]
+fn test_accepts_an_update_signed_by_the_trusted_validators() {
+    let trusted = light_block(1, &["a", "b", "c"]);
+    let target = light_block(8, &["a", "b", "c"]);
+
+    let verdict = verify_natively(&trusted, &target, &options()).unwrap();
+    assert_eq!(verdict, Verdict::Success);
+}
+
+#[test]
+fn test_rejects_an_update_after_the_trusting_period() {
+    let trusted = light_block(1, &["a", "b", "c"]);
+    let target = light_block(8, &["a", "b", "c"]);
+
+    // The trusted block expires before the target block is verified.
+    let short_options = Options {
+        trusting_period: Duration::from_secs(5),
+        ..options()
+    };
+    let verdict = verify_natively(&trusted, &target, &short_options).unwrap();
+    assert!(
+        matches!(
+            verdict,
+            Verdict::Invalid(VerificationErrorDetail::NotWithinTrustPeriod(_))
+        ),
+        "{:?}",
+        verdict
+    );
+
+    // The same update is rejected when verified after the trusting period.
+    let expired_time =
+        (block_time(1) + (options().trusting_period + Duration::from_secs(1))).unwrap();
+    let verdict = verify_natively_at(&trusted, &target, &options(), expired_time).unwrap();
+    assert!(
+        matches!(
+            verdict,
+            Verdict::Invalid(VerificationErrorDetail::NotWithinTrustPeriod(_))
+        ),
+        "{:?}",
+        verdict
+    );
+}
+
+#[test]
+fn test_reports_insufficient_trust_in_an_update_signed_by_other_validators() {
+    let trusted = light_block(1, &["a", "b", "c"]);
+    let target = light_block(8, &["d", "e", "f"]);
+
+    let verdict = verify_natively(&trusted, &target, &options()).unwrap();
+    assert!(
+        matches!(verdict, Verdict::NotEnoughTrust(_)),
+        "{:?}",
+        verdict
+    );
+}
+
+#[test]
+fn test_rejects_an_update_of_another_chain() {
+    let validators = validators(&["a", "b", "c"]);
+    let trusted = sign(header(1, &validators, &validators));
+    let target = sign(header(8, &validators, &validators).chain_id("another-chain"));
+
+    let verdict = verify_natively(&trusted, &target, &options()).unwrap();
+    assert!(
+        matches!(
+            verdict,
+            Verdict::Invalid(VerificationErrorDetail::ChainIdMismatch(_))
+        ),
+        "{:?}",
+        verdict
+    );
+}
+
+#[test]
+fn test_rejects_a_trusted_block_whose_next_validators_do_not_match_its_header() {
+    let mut trusted = light_block(1, &["a", "b", "c"]);
+    trusted.next_validators = light_block(1, &["d", "e", "f"]).next_validators;
+    let target = light_block(8, &["a", "b", "c"]);
+
+    let err = verify_natively(&trusted, &target, &options()).unwrap_err();
+    assert!(
+        matches!(err, NativeVerificationError::NextValidatorsMismatch(1)),
+        "{:?}",
+        err
+    );
+}
@@ -0,0 +1,73 @@
+use std::time::{Duration, Instant};
+use tendermint_operator::watchdog::{ExpiryStatus, ExpiryWatchdog, ExpiryWatchdogArgs};
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+fn watchdog() -> ExpiryWatchdog {
+    // Warns 7, 3 and 1 days before the end of a 14 day trusting period, and is urgent 2 days
+    // before.
+    ExpiryWatchdog::new(&ExpiryWatchdogArgs::default(), 14 * DAY)
+}
+
+#[test]
+fn test_expiry_thresholds() {
+    let mut watchdog = watchdog();
+
+    assert_eq!(watchdog.check(10, 6 * DAY), ExpiryStatus::Healthy);
+    assert_eq!(
+        watchdog.check(10, 7 * DAY),
+        ExpiryStatus::Warning { remaining: 7 * DAY }
+    );
+    assert_eq!(
+        watchdog.check(10, 11 * DAY + Duration::from_secs(1)),
+        ExpiryStatus::Warning {
+            remaining: 3 * DAY - Duration::from_secs(1)
+        }
+    );
+    // The urgent threshold takes precedence over the warning thresholds.
+    assert_eq!(
+        watchdog.check(10, 12 * DAY),
+        ExpiryStatus::Urgent { remaining: 2 * DAY }
+    );
+    assert_eq!(
+        watchdog.check(10, 13 * DAY),
+        ExpiryStatus::Urgent { remaining: DAY }
+    );
+    assert!(watchdog.check(10, 14 * DAY).is_urgent());
+    assert_eq!(
+        watchdog.check(10, 14 * DAY + Duration::from_secs(1)),
+        ExpiryStatus::Expired
+    );
+
+    // Without warning thresholds, blocks are healthy until they are urgent.
+    let mut watchdog = ExpiryWatchdog::new(
+        &ExpiryWatchdogArgs {
+            expiry_warning_thresholds: vec![],
+            urgent_expiry_threshold: DAY.as_secs(),
+        },
+        14 * DAY,
+    );
+    assert_eq!(watchdog.check(10, 12 * DAY), ExpiryStatus::Healthy);
+    assert!(watchdog.check(10, 13 * DAY).is_urgent());
+}
+
+#[test]
+fn test_time_until_urgent() {
+    let mut watchdog = watchdog();
+
+    assert_eq!(watchdog.time_until_urgent(Duration::ZERO), Some(12 * DAY));
+    assert_eq!(watchdog.time_until_urgent(11 * DAY), Some(DAY));
+    assert_eq!(watchdog.time_until_urgent(12 * DAY), None);
+    assert_eq!(watchdog.time_until_urgent(15 * DAY), None);
+
+    // The operator wakes up when the checked block becomes urgent, before its next poll.
+    let before = Instant::now();
+    watchdog.check(10, 12 * DAY - Duration::from_secs(30));
+    let urgent_at = watchdog.urgent_at().unwrap();
+    assert!(urgent_at >= before + Duration::from_secs(30));
+    assert!(urgent_at <= Instant::now() + Duration::from_secs(30));
+
+    // Once urgent, it's updated right away.
+    watchdog.check(10, 12 * DAY);
+    assert_eq!(watchdog.urgent_at(), None);
+}
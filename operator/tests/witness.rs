@@ -0,0 +1,169 @@
+mod common;
+
+use common::MockServer;
+use std::time::Duration;
+use tendermint::{
+    account,
+    block::{
+        self, header::Version, signed_header::SignedHeader, Commit, CommitSig, Header, Height,
+        Round,
+    },
+    node, AppHash, Hash, Signature, Time,
+};
+use tendermint_light_client_verifier::types::{LightBlock, ValidatorSet};
+use tendermint_operator::util::{RetryConfig, TendermintRPCClient, WitnessError};
+
+/// A light block at the given height, whose header differs with the app hash.
+fn light_block(height: u32, app_hash: u8) -> LightBlock {
+    let time = Time::parse_from_rfc3339("2024-01-01T00:00:00Z").unwrap();
+    let header = Header {
+        version: Version { block: 11, app: 1 },
+        chain_id: "mocha-4".parse().unwrap(),
+        height: Height::from(height),
+        time,
+        last_block_id: None,
+        last_commit_hash: None,
+        data_hash: None,
+        validators_hash: Hash::Sha256([1; 32]),
+        next_validators_hash: Hash::Sha256([1; 32]),
+        consensus_hash: Hash::Sha256([2; 32]),
+        app_hash: AppHash::try_from(vec![app_hash; 32]).unwrap(),
+        last_results_hash: None,
+        evidence_hash: None,
+        proposer_address: account::Id::new([0; 20]),
+    };
+    let commit = Commit {
+        height: header.height,
+        round: Round::default(),
+        block_id: block::Id {
+            hash: header.hash(),
+            part_set_header: Default::default(),
+        },
+        // The RPC encoding of a commit without signatures can't be decoded.
+        signatures: vec![CommitSig::BlockIdFlagCommit {
+            validator_address: account::Id::new([0; 20]),
+            timestamp: time,
+            signature: Signature::new([1; 64]).unwrap(),
+        }],
+    };
+
+    LightBlock::new(
+        SignedHeader::new(header, commit).unwrap(),
+        ValidatorSet::without_proposer(vec![]),
+        ValidatorSet::without_proposer(vec![]),
+        node::Id::new([0; 20]),
+    )
+}
+
+/// A `/commit` response for the light block.
+fn commit_response(light_block: &LightBlock) -> (u16, String) {
+    (
+        200,
+        format!(
+            r#"{{"result":{{"signed_header":{},"canonical":true}}}}"#,
+            serde_json::to_string(&light_block.signed_header).unwrap()
+        ),
+    )
+}
+
+/// A witness serving the given light blocks at their heights.
+async fn witness(light_blocks: Vec<LightBlock>) -> MockServer {
+    MockServer::start(move |_, request| {
+        light_blocks
+            .iter()
+            .find(|light_block| {
+                request.contains(&format!(
+                    "height={}&",
+                    light_block.signed_header.header.height
+                ))
+            })
+            .map(commit_response)
+            .unwrap_or((404, "not found".to_string()))
+    })
+    .await
+}
+
+fn client(witnesses: &[&MockServer]) -> TendermintRPCClient {
+    TendermintRPCClient::new(vec!["http://127.0.0.1:1".to_string()])
+        .unwrap()
+        .with_witnesses(witnesses.iter().map(|witness| witness.url()).collect())
+        .with_retry_config(RetryConfig {
+            max_attempts: 1,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(1),
+            backoff_multiplier: 2,
+        })
+}
+
+#[tokio::test]
+async fn test_matching_witnesses_confirm_the_path() {
+    let path = vec![light_block(10, 0), light_block(20, 0)];
+    let first = witness(path.clone()).await;
+    let second = witness(path.clone()).await;
+
+    client(&[&first, &second])
+        .with_min_witness_matches(2)
+        .check_witnesses(&path)
+        .await
+        .unwrap();
+    assert_eq!(first.requests(), 2);
+    assert_eq!(second.requests(), 2);
+}
+
+#[tokio::test]
+async fn test_reports_a_diverging_intermediate_block() {
+    let path = vec![light_block(10, 0), light_block(20, 0)];
+    let forked = witness(vec![light_block(10, 1), light_block(20, 0)]).await;
+
+    let err = client(&[&forked]).check_witnesses(&path).await.unwrap_err();
+
+    let WitnessError::Divergence(divergence) = err else {
+        panic!("expected a divergence, got {:?}", err);
+    };
+    assert_eq!(divergence.height, 10);
+    assert_eq!(divergence.witness_url, forked.url());
+    assert_eq!(divergence.primary_hash, path[0].signed_header.header.hash());
+    assert_eq!(
+        divergence.witness_hash,
+        light_block(10, 1).signed_header.header.hash()
+    );
+}
+
+#[tokio::test]
+async fn test_unreachable_witnesses_do_not_confirm_blocks() {
+    let path = vec![light_block(10, 0)];
+    let unreachable = MockServer::start(|_, _| (502, "bad gateway".to_string())).await;
+
+    let err = client(&[&unreachable])
+        .check_witnesses(&path)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        WitnessError::Unconfirmed {
+            height: 10,
+            matches: 0,
+            required: 1
+        }
+    ));
+
+    // A reachable witness confirms the block unless more matches are required.
+    let healthy = witness(path.clone()).await;
+    client(&[&unreachable, &healthy])
+        .check_witnesses(&path)
+        .await
+        .unwrap();
+    let err = client(&[&unreachable, &healthy])
+        .with_min_witness_matches(2)
+        .check_witnesses(&path)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        WitnessError::Unconfirmed {
+            matches: 1,
+            required: 2,
+            ..
+        }
+    ));
+}
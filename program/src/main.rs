@@ -3,9 +3,10 @@ sp1_zkvm::entrypoint!(main);
 
 use alloy_sol_types::{sol, SolValue};
 use core::time::Duration;
+use sha2::{Digest, Sha256};
 use tendermint_light_client_verifier::{
     options::Options,
-    types::{LightBlock, TrustThreshold},
+    types::{Hash, LightBlock, Time},
     ProdVerifier, Verdict, Verifier,
 };
 
@@ -15,64 +16,240 @@ sol! {
         uint64 targetHeight;
         bytes32 trustedHeaderHash;
         bytes32 targetHeaderHash;
+        uint64 trustThresholdNumerator;
+        uint64 trustThresholdDenominator;
+        uint64 trustingPeriod;
+        uint64 clockDrift;
+        bytes32 chainIdHash;
+        bytes targetAppHash;
+        bytes32 targetValidatorsHash;
+        bytes32 targetNextValidatorsHash;
+    }
+
+    struct MisbehaviourOutput {
+        bytes32 tag;
+        uint64 trustedHeight;
+        bytes32 trustedHeaderHash;
+        uint64 conflictHeight;
+        bytes32 conflictingHeaderHash1;
+        bytes32 conflictingHeaderHash2;
+        uint64 trustThresholdNumerator;
+        uint64 trustThresholdDenominator;
+        uint64 trustingPeriod;
+        uint64 clockDrift;
+        bytes32 chainIdHash;
     }
 }
 
+/// Proves an update of the light client along a path of light blocks.
+const MODE_UPDATE: u8 = 0;
+/// Proves misbehaviour, i.e. two conflicting headers at the same height that both verify against
+/// the same common block, which verifies along a path from the trusted block.
+const MODE_MISBEHAVIOUR: u8 = 1;
+
+/// Prefix of the misbehaviour public values, so they can't be mistaken for an update. Matches
+/// `MISBEHAVIOUR_TAG` in the SP1Tendermint contract.
+const MISBEHAVIOUR_TAG: &[u8] = b"SP1Tendermint/Misbehaviour";
+
 fn main() {
-    // Read in 2 encoded vectors of two light blocks from the zkVM's stdin.
-    let encoded_1 = sp1_zkvm::io::read_vec();
-    let encoded_2 = sp1_zkvm::io::read_vec();
+    // Read in the mode of the program from the zkVM's stdin.
+    let mode = sp1_zkvm::io::read::<u8>();
+    match mode {
+        MODE_UPDATE => update(),
+        MODE_MISBEHAVIOUR => misbehaviour(),
+        mode => panic!("Unknown mode {}", mode),
+    }
+}
+
+/// Verifies a path of light blocks from a trusted block to a target block and commits the update.
+fn update() {
+    // Read in the encoded path of light blocks from the zkVM's stdin. The first light block is the
+    // trusted block and the last light block is the target block.
+    let encoded_path = sp1_zkvm::io::read_vec();
+    // Read in the encoded light client options used to verify the update.
+    let encoded_opt = sp1_zkvm::io::read_vec();
+
+    // Decode the light blocks and the options.
+    let light_blocks: Vec<LightBlock> = serde_cbor::from_slice(&encoded_path).unwrap();
+    let opt: Options = serde_cbor::from_slice(&encoded_opt).unwrap();
+    assert!(
+        light_blocks.len() >= 2,
+        "Expected at least 2 light blocks, got {}",
+        light_blocks.len()
+    );
+    let light_block_1 = light_blocks.first().unwrap();
+    let light_block_2 = light_blocks.last().unwrap();
+
+    let vp = ProdVerifier::default();
+
+    // Every hop is verified at the same time, relative to the target block.
+    let verify_time = verify_time(light_block_2);
+
+    // Verify each hop of the path, where every verified block becomes the trusted block of the next
+    // hop.
+    for hop in light_blocks.windows(2) {
+        verify_hop(&vp, &hop[0], &hop[1], &opt, verify_time);
+    }
+
+    // Now that we have verified our proof, we commit the header hashes, the options, the chain ID and
+    // the target header's app and validator set hashes to the zkVM to expose them as public values.
+    // The chain ID is committed as its SHA-256 hash. The app hash is committed as is, since its
+    // length depends on the chain's application and it is the root that state proofs verify against.
+    let target_header = &light_block_2.signed_header.header;
+
+    let output = TendermintOutput {
+        trustedHeight: light_block_1.signed_header.header.height.value(),
+        targetHeight: light_block_2.signed_header.header.height.value(),
+        trustedHeaderHash: header_hash(light_block_1).into(),
+        targetHeaderHash: header_hash(light_block_2).into(),
+        trustThresholdNumerator: opt.trust_threshold.numerator(),
+        trustThresholdDenominator: opt.trust_threshold.denominator(),
+        trustingPeriod: opt.trusting_period.as_secs(),
+        clockDrift: opt.clock_drift.as_secs(),
+        chainIdHash: chain_id_hash(light_block_2).into(),
+        targetAppHash: target_header.app_hash.as_bytes().to_vec().into(),
+        targetValidatorsHash: hash_bytes(&target_header.validators_hash).into(),
+        targetNextValidatorsHash: hash_bytes(&target_header.next_validators_hash).into(),
+    };
+
+    sp1_zkvm::io::commit_slice(&output.abi_encode());
+}
+
+/// Verifies that two conflicting light blocks at the same height both verify against a common
+/// block reached along a path from a trusted block, which is evidence of a light client attack,
+/// and commits the misbehaviour.
+fn misbehaviour() {
+    // Read in the encoded path from the trusted light block to the common light block and the two
+    // conflicting light blocks. The path only contains the trusted light block if it is the common
+    // light block.
+    let encoded_common_path = sp1_zkvm::io::read_vec();
+    let encoded_conflicting_1 = sp1_zkvm::io::read_vec();
+    let encoded_conflicting_2 = sp1_zkvm::io::read_vec();
+    // Read in the encoded light client options used to verify the conflicting light blocks.
+    let encoded_opt = sp1_zkvm::io::read_vec();
+
+    let common_path: Vec<LightBlock> = serde_cbor::from_slice(&encoded_common_path).unwrap();
+    let conflicting_1: LightBlock = serde_cbor::from_slice(&encoded_conflicting_1).unwrap();
+    let conflicting_2: LightBlock = serde_cbor::from_slice(&encoded_conflicting_2).unwrap();
+    let opt: Options = serde_cbor::from_slice(&encoded_opt).unwrap();
+    assert!(
+        !common_path.is_empty(),
+        "Expected at least the trusted light block"
+    );
+    let trusted = common_path.first().unwrap();
+    let common = common_path.last().unwrap();
 
-    // Decode the light blocks.
-    let light_block_1: LightBlock = serde_cbor::from_slice(&encoded_1).unwrap();
-    let light_block_2: LightBlock = serde_cbor::from_slice(&encoded_2).unwrap();
+    // The conflicting headers must be different headers at the same height.
+    let conflict_height = conflicting_1.signed_header.header.height.value();
+    assert_eq!(
+        conflict_height,
+        conflicting_2.signed_header.header.height.value(),
+        "Conflicting light blocks must be at the same height"
+    );
+    let conflicting_hash_1 = header_hash(&conflicting_1);
+    let conflicting_hash_2 = header_hash(&conflicting_2);
+    assert_ne!(
+        conflicting_hash_1, conflicting_hash_2,
+        "Conflicting light blocks must have different headers"
+    );
 
+    // The common block must verify along the path from the trusted block, where every hop is
+    // verified at the same time, relative to the first conflicting block.
     let vp = ProdVerifier::default();
-    let opt = Options {
-        trust_threshold: TrustThreshold::TWO_THIRDS,
-        // 2 week trusting period.
-        trusting_period: Duration::from_secs(14 * 24 * 60 * 60),
-        clock_drift: Default::default(),
+    let path_verify_time = verify_time(&conflicting_1);
+    for hop in common_path.windows(2) {
+        verify_hop(&vp, &hop[0], &hop[1], &opt, path_verify_time);
+    }
+
+    // Both conflicting headers must be sufficiently signed by the validators of the common block.
+    verify_hop(
+        &vp,
+        common,
+        &conflicting_1,
+        &opt,
+        verify_time(&conflicting_1),
+    );
+    verify_hop(
+        &vp,
+        common,
+        &conflicting_2,
+        &opt,
+        verify_time(&conflicting_2),
+    );
+
+    let mut tag = [0u8; 32];
+    tag[..MISBEHAVIOUR_TAG.len()].copy_from_slice(MISBEHAVIOUR_TAG);
+
+    let output = MisbehaviourOutput {
+        tag: tag.into(),
+        trustedHeight: trusted.signed_header.header.height.value(),
+        trustedHeaderHash: header_hash(trusted).into(),
+        conflictHeight: conflict_height,
+        conflictingHeaderHash1: conflicting_hash_1.into(),
+        conflictingHeaderHash2: conflicting_hash_2.into(),
+        trustThresholdNumerator: opt.trust_threshold.numerator(),
+        trustThresholdDenominator: opt.trust_threshold.denominator(),
+        trustingPeriod: opt.trusting_period.as_secs(),
+        clockDrift: opt.clock_drift.as_secs(),
+        chainIdHash: chain_id_hash(trusted).into(),
     };
 
+    sp1_zkvm::io::commit_slice(&output.abi_encode());
+}
+
+/// Verifies the untrusted light block against the trusted light block, panicking if it fails.
+fn verify_hop(
+    vp: &ProdVerifier,
+    trusted: &LightBlock,
+    untrusted: &LightBlock,
+    opt: &Options,
+    verify_time: Time,
+) {
     // Verify update header doesn't check this property.
     assert_eq!(
-        light_block_1.next_validators.hash(),
-        light_block_1.as_trusted_state().next_validators_hash
+        trusted.next_validators.hash(),
+        trusted.as_trusted_state().next_validators_hash
     );
 
-    let verify_time = light_block_2.time() + Duration::from_secs(20);
     let verdict = vp.verify_update_header(
-        light_block_2.as_untrusted_state(),
-        light_block_1.as_trusted_state(),
-        &opt,
-        verify_time.unwrap(),
+        untrusted.as_untrusted_state(),
+        trusted.as_trusted_state(),
+        opt,
+        verify_time,
     );
 
     match verdict {
         Verdict::Success => {
             println!(
                 "Verified light client update from height {} to height {}!",
-                light_block_1.signed_header.header.height.value(),
-                light_block_2.signed_header.header.height.value()
+                trusted.signed_header.header.height.value(),
+                untrusted.signed_header.header.height.value()
             );
         }
         v => panic!("Failed to verify light client update: {:?}", v),
     }
+}
 
-    // Now that we have verified our proof, we commit the header hashes to the zkVM to expose
-    // them as public values.
-    let header_hash_1 = light_block_1.signed_header.header.hash();
-    let header_hash_1: [u8; 32] = header_hash_1.as_bytes().to_vec().try_into().unwrap();
-    let header_hash_2 = light_block_2.signed_header.header.hash();
-    let header_hash_2: [u8; 32] = header_hash_2.as_bytes().to_vec().try_into().unwrap();
+/// The time at which an update to the given light block is verified.
+fn verify_time(light_block: &LightBlock) -> Time {
+    (light_block.time() + Duration::from_secs(20)).unwrap()
+}
 
-    let output = TendermintOutput {
-        trustedHeight: light_block_1.signed_header.header.height.value(),
-        targetHeight: light_block_2.signed_header.header.height.value(),
-        trustedHeaderHash: header_hash_1.into(),
-        targetHeaderHash: header_hash_2.into(),
-    };
+/// The hash of the light block's header.
+fn header_hash(light_block: &LightBlock) -> [u8; 32] {
+    let header_hash = light_block.signed_header.header.hash();
+    header_hash.as_bytes().to_vec().try_into().unwrap()
+}
 
-    sp1_zkvm::io::commit_slice(&output.abi_encode());
+/// The bytes of the hash, or zero if there is none, e.g. for an empty validator set.
+fn hash_bytes(hash: &Hash) -> [u8; 32] {
+    match hash {
+        Hash::Sha256(bytes) => *bytes,
+        Hash::None => [0; 32],
+    }
+}
+
+/// The SHA-256 hash of the light block's chain ID.
+fn chain_id_hash(light_block: &LightBlock) -> [u8; 32] {
+    Sha256::digest(light_block.signed_header.header.chain_id.as_bytes()).into()
 }